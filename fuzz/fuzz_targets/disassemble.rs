@@ -0,0 +1,49 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+#![no_main]
+
+use aluvm::isa::{Instr, Instruction};
+use aluvm::{Lib, LibId};
+use amplify::confinement::{SmallBlob, TinyOrdSet};
+use libfuzzer_sys::fuzz_target;
+
+// The first input byte selects how many of the following bytes go to the data segment; the rest
+// of the input is used as the code segment.
+fuzz_target!(|input: &[u8]| {
+    let Some((&split, rest)) = input.split_first() else {
+        return;
+    };
+    let (data, code) = rest.split_at((split as usize).min(rest.len()));
+    let (Ok(code), Ok(data)) = (SmallBlob::try_from(code.to_vec()), SmallBlob::try_from(data.to_vec()))
+    else {
+        return;
+    };
+    let libs = TinyOrdSet::from_iter_checked([LibId::from([0xA5u8; 32])]);
+
+    let lib = Lib::from_bytecode_unchecked(Instr::<LibId>::isa_ext(), code, data, libs);
+    let _ = lib.disassemble::<Instr<LibId>>();
+    let _ = lib.to_dot::<Instr<LibId>>();
+    let _ = Lib::from_bytecode_validated::<Instr<LibId>>(lib.isae, lib.code, lib.data, lib.libs);
+});
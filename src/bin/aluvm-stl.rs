@@ -32,7 +32,7 @@ fn main() {
         .serialize(
             format,
             dir,
-            "0.1.0",
+            "0.2.0",
             Some(
                 "
   Description: AluVM data type library
@@ -22,6 +22,7 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
+use alloc::collections::BTreeSet;
 use core::fmt::{self, Debug, Formatter};
 
 use amplify::confinement::ConfinedVec;
@@ -116,6 +117,14 @@ pub struct Core<
     /// - [`Core::cp`] register
     pub(super) cs: ConfinedVec<Site<Id>, 0, CALL_STACK_SIZE>,
 
+    /// Call stack size limit, which can't exceed `CALL_STACK_SIZE`.
+    ///
+    /// # See also
+    ///
+    /// - [`CoreConfig::call_stack_size`]
+    /// - [`Core::cs`] register
+    pub(super) cs_lim: u16,
+
     /// Core extension module.
     pub cx: Cx,
 }
@@ -130,18 +139,39 @@ pub struct CoreConfig {
     pub halt: bool,
     /// Initial value for the `CL` register.
     pub complexity_lim: Option<u64>,
+    /// Maximum depth of the call stack.
+    ///
+    /// Values above the call stack size of the [`Core`] are reduced to it.
+    ///
+    /// Defaults to [`CALL_STACK_SIZE_MAX`] when absent from serialized configurations created
+    /// before the field was introduced.
+    #[cfg_attr(feature = "serde", serde(default = "CoreConfig::default_call_stack_size"))]
+    pub call_stack_size: u16,
+}
+
+impl CoreConfig {
+    #[cfg(feature = "serde")]
+    fn default_call_stack_size() -> u16 { CALL_STACK_SIZE_MAX }
 }
 
 impl Default for CoreConfig {
     /// Sets
     /// - [`CoreConfig::halt`] to `true`,
     /// - [`CoreConfig::complexity_lim`] to `None`
+    /// - [`CoreConfig::call_stack_size`] to [`CALL_STACK_SIZE_MAX`]
     ///
     /// # See also
     ///
     /// - [`CoreConfig::halt`]
     /// - [`CoreConfig::complexity_lim`]
-    fn default() -> Self { CoreConfig { halt: true, complexity_lim: None } }
+    /// - [`CoreConfig::call_stack_size`]
+    fn default() -> Self {
+        CoreConfig {
+            halt: true,
+            complexity_lim: None,
+            call_stack_size: CALL_STACK_SIZE_MAX,
+        }
+    }
 }
 
 impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Default
@@ -150,6 +180,11 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Default
     fn default() -> Self { Core::new() }
 }
 
+/// Error initializing core with [`Core::with_registers`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("register {0} is initialized more than once")]
+pub struct InitError<R: Register>(pub R);
+
 impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_STACK_SIZE> {
     /// Initializes registers. Sets `st0` to `true`, counters to zero, call stack to empty and the
     /// rest of registers to `None` value.
@@ -164,6 +199,7 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
     /// Initializes registers using a configuration object [`CoreConfig`].
     pub fn with(config: CoreConfig, cx_config: Cx::Config) -> Self {
         assert!(CALL_STACK_SIZE <= CALL_STACK_SIZE_MAX as usize, "Call stack size is too large");
+        let cs_lim = config.call_stack_size.min(CALL_STACK_SIZE as u16);
         Core {
             ch: config.halt,
             ck: Status::Ok,
@@ -172,15 +208,38 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
             cy: 0,
             ca: 0,
             cl: config.complexity_lim,
-            cs: ConfinedVec::with_capacity(CALL_STACK_SIZE),
+            cs: ConfinedVec::with_capacity(cs_lim as usize),
+            cs_lim,
             cx: Cx::with(cx_config),
         }
     }
 
+    /// Initializes registers using a configuration object [`CoreConfig`] and pre-loads the core
+    /// extension registers with the provided values.
+    ///
+    /// Used by host environments to inject external data into the program before its execution.
+    /// Errors if some of the registers is listed more than once.
+    pub fn with_registers(
+        config: CoreConfig,
+        cx_config: Cx::Config,
+        initial: &[(Cx::Reg, <Cx::Reg as Register>::Value)],
+    ) -> Result<Self, InitError<Cx::Reg>> {
+        let mut core = Self::with(config, cx_config);
+        let mut seen = BTreeSet::new();
+        for (reg, val) in initial {
+            if !seen.insert(*reg) {
+                return Err(InitError(*reg));
+            }
+            core.cx.set(*reg, *val);
+        }
+        Ok(core)
+    }
+
     pub fn reset(&mut self) {
         let mut new = Self::new();
         new.ch = self.ch;
         new.cl = self.cl;
+        new.cs_lim = self.cs_lim;
         new.cx.reset();
         *self = new;
     }
@@ -231,6 +290,7 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
             ca: core.ca,
             cl: core.cl,
             cs: core.cs,
+            cs_lim: core.cs_lim,
             cx: core.cx.into(),
         }
     }
@@ -245,7 +305,29 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
             ca: self.ca,
             cl: self.cl,
             cs: self.cs,
+            cs_lim: self.cs_lim,
             cx,
         }
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use serde::de::value::{Error, MapDeserializer};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[test]
+    fn config_without_call_stack_size() {
+        let fields = MapDeserializer::<_, Error>::new([("halt", false)].into_iter());
+        assert_eq!(
+            CoreConfig::deserialize(fields),
+            Ok(CoreConfig {
+                halt: false,
+                complexity_lim: None,
+                call_stack_size: CALL_STACK_SIZE_MAX,
+            })
+        );
+    }
+}
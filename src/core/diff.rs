@@ -0,0 +1,272 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+use super::{Core, CoreExt, Register, Site, SiteId, Status};
+
+/// Change in the value of a single register.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum RegisterChange<Id: SiteId, Reg: Register> {
+    /// Change of the `CH` register.
+    Ch { old: bool, new: bool },
+    /// Change of the `CK` register.
+    Ck { old: Status, new: Status },
+    /// Change of the `CF` register.
+    Cf { old: u64, new: u64 },
+    /// Change of the `CO` register.
+    Co { old: bool, new: bool },
+    /// Change of the `CY` register.
+    Cy { old: u16, new: u16 },
+    /// Change of the `CA` register.
+    Ca { old: u64, new: u64 },
+    /// Change of the `CL` register.
+    Cl { old: Option<u64>, new: Option<u64> },
+    /// Change of the call stack.
+    Cs {
+        old: Vec<Site<Id>>,
+        new: Vec<Site<Id>>,
+    },
+    /// Change of a core extension register; `None` stands for an unset register.
+    Cx {
+        reg: Reg,
+        old: Option<Reg::Value>,
+        new: Option<Reg::Value>,
+    },
+}
+
+impl<Id: SiteId, Reg: Register> Display for RegisterChange<Id, Reg> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fn opt<T: Display>(f: &mut Formatter<'_>, val: &Option<T>) -> fmt::Result {
+            match val {
+                Some(val) => write!(f, "{val}"),
+                None => f.write_str("~"),
+            }
+        }
+        fn cs<Id: SiteId>(f: &mut Formatter<'_>, cs: &[Site<Id>]) -> fmt::Result {
+            for (no, site) in cs.iter().enumerate() {
+                if no > 0 {
+                    f.write_str(" ")?;
+                }
+                write!(f, "{site}")?;
+            }
+            Ok(())
+        }
+
+        match self {
+            RegisterChange::Ch { old, new } => write!(f, "CH: {old} → {new}"),
+            RegisterChange::Ck { old, new } => write!(f, "CK: {old} → {new}"),
+            RegisterChange::Cf { old, new } => write!(f, "CF: {old} → {new}"),
+            RegisterChange::Co { old, new } => write!(f, "CO: {old} → {new}"),
+            RegisterChange::Cy { old, new } => write!(f, "CY: {old} → {new}"),
+            RegisterChange::Ca { old, new } => write!(f, "CA: {old} → {new}"),
+            RegisterChange::Cl { old, new } => {
+                f.write_str("CL: ")?;
+                opt(f, old)?;
+                f.write_str(" → ")?;
+                opt(f, new)
+            }
+            RegisterChange::Cs { old, new } => {
+                f.write_str("CS: ")?;
+                cs(f, old)?;
+                f.write_str(" → ")?;
+                cs(f, new)
+            }
+            RegisterChange::Cx { reg, old, new } => {
+                write!(f, "{reg}: ")?;
+                opt(f, old)?;
+                f.write_str(" → ")?;
+                opt(f, new)
+            }
+        }
+    }
+}
+
+/// Values of the control registers and of selected core extension registers, taken from a core
+/// state to detect register changes with [`RegisterDiff::since`].
+#[derive(Clone, Debug)]
+pub struct RegisterSnapshot<Id: SiteId, Reg: Register> {
+    ch: bool,
+    ck: Status,
+    cf: u64,
+    co: bool,
+    cy: u16,
+    ca: u64,
+    cl: Option<u64>,
+    cs: Vec<Site<Id>>,
+    cx: Vec<(Reg, Option<Reg::Value>)>,
+}
+
+impl<Id: SiteId, Reg: Register> RegisterSnapshot<Id, Reg> {
+    /// Takes values of all control registers and of the core extension registers listed in
+    /// `regs`.
+    ///
+    /// Unlike cloning the whole core, the snapshot copies only the values which are compared
+    /// afterward.
+    pub fn take<Cx: CoreExt<Reg = Reg>, const CALL_STACK_SIZE: usize>(
+        core: &Core<Id, Cx, CALL_STACK_SIZE>,
+        regs: impl IntoIterator<Item = Reg>,
+    ) -> Self {
+        RegisterSnapshot {
+            ch: core.ch,
+            ck: core.ck,
+            cf: core.cf,
+            co: core.co,
+            cy: core.cy,
+            ca: core.ca,
+            cl: core.cl,
+            cs: core.cs.iter().copied().collect(),
+            cx: regs.into_iter().map(|reg| (reg, core.get(reg))).collect(),
+        }
+    }
+}
+
+/// Difference between two states of the core registers.
+#[derive(Clone, Debug)]
+pub struct RegisterDiff<Id: SiteId, Reg: Register>(Vec<RegisterChange<Id, Reg>>);
+
+impl<Id: SiteId, Reg: Register> PartialEq for RegisterDiff<Id, Reg>
+where RegisterChange<Id, Reg>: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+impl<Id: SiteId, Reg: Register> Eq for RegisterDiff<Id, Reg> where RegisterChange<Id, Reg>: Eq {}
+
+impl<Id: SiteId, Reg: Register> Default for RegisterDiff<Id, Reg> {
+    fn default() -> Self { RegisterDiff(Vec::new()) }
+}
+
+impl<Id: SiteId, Reg: Register> RegisterDiff<Id, Reg> {
+    /// Detects all control registers, and core extension registers from the `regs` list, which
+    /// values differ between two core states.
+    ///
+    /// Core extensions don't provide a way to enumerate their registers, thus only the registers
+    /// listed in `regs` are compared.
+    pub fn between<Cx: CoreExt<Reg = Reg>, const CALL_STACK_SIZE: usize>(
+        before: &Core<Id, Cx, CALL_STACK_SIZE>,
+        after: &Core<Id, Cx, CALL_STACK_SIZE>,
+        regs: impl IntoIterator<Item = Reg>,
+    ) -> Self
+    where
+        Reg::Value: PartialEq,
+    {
+        Self::since(&RegisterSnapshot::take(before, regs), after)
+    }
+
+    /// Detects all registers from the `snapshot` which values differ in the current core state.
+    pub fn since<Cx: CoreExt<Reg = Reg>, const CALL_STACK_SIZE: usize>(
+        snapshot: &RegisterSnapshot<Id, Reg>,
+        core: &Core<Id, Cx, CALL_STACK_SIZE>,
+    ) -> Self
+    where
+        Reg::Value: PartialEq,
+    {
+        let before = snapshot;
+        let mut diff = RegisterDiff::default();
+        let mut push = |changed: bool, change: RegisterChange<Id, Reg>| {
+            if changed {
+                diff.0.push(change);
+            }
+        };
+        push(before.ch != core.ch, RegisterChange::Ch { old: before.ch, new: core.ch });
+        push(before.ck != core.ck, RegisterChange::Ck { old: before.ck, new: core.ck });
+        push(before.cf != core.cf, RegisterChange::Cf { old: before.cf, new: core.cf });
+        push(before.co != core.co, RegisterChange::Co { old: before.co, new: core.co });
+        push(before.cy != core.cy, RegisterChange::Cy { old: before.cy, new: core.cy });
+        push(before.ca != core.ca, RegisterChange::Ca { old: before.ca, new: core.ca });
+        push(before.cl != core.cl, RegisterChange::Cl { old: before.cl, new: core.cl });
+        if before.cs.as_slice() != core.cs.as_slice() {
+            push(true, RegisterChange::Cs {
+                old: before.cs.clone(),
+                new: core.cs.iter().copied().collect(),
+            });
+        }
+        for &(reg, old) in &before.cx {
+            let new = core.get(reg);
+            push(old != new, RegisterChange::Cx { reg, old, new });
+        }
+        diff
+    }
+
+    /// Detects whether both core states have the same values in all compared registers.
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns the number of changed registers.
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Iterates over all register changes.
+    pub fn iter(&self) -> impl Iterator<Item = &RegisterChange<Id, Reg>> { self.0.iter() }
+}
+
+impl<Id: SiteId, Reg: Register> Display for RegisterDiff<Id, Reg> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for change in &self.0 {
+            writeln!(f, "{change}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::test_utils::{Reg, Regs};
+    use crate::isa::{CtrlInstr, Instr};
+    use crate::{Lib, LibId, LibSite, Vm};
+
+    #[test]
+    fn register_diff() {
+        let lib =
+            Lib::assemble(&[Instr::Ctrl(CtrlInstr::NotCo), Instr::Ctrl(CtrlInstr::Stop)]).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let before = vm.core.clone();
+        assert!(RegisterDiff::between(&before, &vm.core, []).is_empty());
+
+        vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        let diff = RegisterDiff::between(&before, &vm.core, []);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff.iter().next(), Some(&RegisterChange::Co { old: false, new: true }));
+        assert_eq!(diff.to_string(), "CO: false → true\n");
+    }
+
+    #[test]
+    fn extension_registers() {
+        let before = Core::<LibId, Regs>::new();
+        let mut after = before.clone();
+        after.cx.set(Reg(0), 0x5A);
+        after.cl = Some(1000);
+        assert!(RegisterDiff::between(&before, &after, [])
+            .iter()
+            .all(|change| !matches!(change, RegisterChange::Cx { .. })));
+
+        let diff = RegisterDiff::between(&before, &after, [Reg(0), Reg(1)]);
+        assert_eq!(diff.iter().collect::<Vec<_>>(), [
+            &RegisterChange::Cl { old: None, new: Some(1000) },
+            &RegisterChange::Cx { reg: Reg(0), old: None, new: Some(0x5A) },
+        ]);
+        assert_eq!(diff.to_string(), "CL: ~ → 1000\nR0: ~ → 90\n");
+    }
+}
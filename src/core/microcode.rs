@@ -58,12 +58,24 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
     /// Return size of the call stack.
     pub fn cp(&self) -> u16 { self.cs.len() as u16 }
 
+    /// Return depth of the call stack.
+    pub fn call_stack_depth(&self) -> usize { self.cs.len() }
+
+    /// Iterate over the return locations in the call stack, from the bottom to the top.
+    pub fn iter_call_stack(&self) -> impl Iterator<Item = Site<Id>> + '_ { self.cs.iter().copied() }
+
+    /// Return maximum size of the call stack.
+    pub fn cs_lim(&self) -> u16 { self.cs_lim }
+
     /// Push a location to a call stack.
     ///
     /// # Returns
     ///
-    /// Top of the call stack.
+    /// Top of the call stack, or `None` if the call stack size limit is reached.
     pub fn push_cs(&mut self, from: Site<Id>) -> Option<u16> {
+        if self.cp() >= self.cs_lim {
+            return None;
+        }
         self.cs.push(from).ok()?;
         Some(self.cp())
     }
@@ -71,6 +83,9 @@ impl<Id: SiteId, Cx: CoreExt, const CALL_STACK_SIZE: usize> Core<Id, Cx, CALL_ST
     /// Pops a call stack item.
     pub fn pop_cs(&mut self) -> Option<Site<Id>> { self.cs.pop() }
 
+    /// Return accumulated complexity value.
+    pub fn ca(&self) -> u64 { self.ca }
+
     /// Return complexity limit value.
     pub fn cl(&self) -> Option<u64> { self.cl }
 
@@ -26,8 +26,10 @@
 
 #[allow(clippy::module_inception)]
 mod core;
+mod diff;
 mod microcode;
 mod util;
 
-pub use self::core::{Core, CoreConfig, CoreExt, CALL_STACK_SIZE_MAX};
+pub use self::core::{Core, CoreConfig, CoreExt, InitError, CALL_STACK_SIZE_MAX};
+pub use self::diff::{RegisterChange, RegisterDiff, RegisterSnapshot};
 pub use self::util::{NoExt, NoRegs, Register, Site, SiteId, Status};
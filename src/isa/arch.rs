@@ -33,6 +33,12 @@ use crate::LIB_NAME_ALUVM;
 
 pub const ISA_ID_MAX_LEN: usize = 16;
 
+/// ISA extension providing debugging instructions (software breakpoints).
+///
+/// Libraries must declare this extension in their ISAE segment for the breakpoint instructions to
+/// be executed; otherwise they are treated as reserved instructions.
+pub const ISA_DEBUG: &str = "DEBUG";
+
 #[macro_export]
 macro_rules! isa {
     ($id:literal) => {
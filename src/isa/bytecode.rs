@@ -97,6 +97,22 @@ pub trait BytecodeRead<Id: SiteId> {
     fn seek(&mut self, byte_pos: u16) -> Result<u16, CodeEofError>;
     /// Return whether end of the bytecode is reached.
     fn is_eof(&self) -> bool;
+    /// Return number of bytes remaining in the bytecode starting from the current cursor position.
+    /// Does not account for bits.
+    ///
+    /// The default implementation doesn't know the bytecode length and returns an upper bound,
+    /// assuming the bytecode spans up to `u16::MAX` bytes. Readers knowing the bytecode length
+    /// should override it, otherwise [`BytecodeRead::is_at_boundary`] doesn't detect truncated
+    /// instructions.
+    fn remaining_bytes(&self) -> u16 {
+        if self.is_eof() {
+            return 0;
+        }
+        u16::MAX - self.pos()
+    }
+    /// Return whether at least `count` bytes remain in the bytecode starting from the current
+    /// cursor position.
+    fn is_at_boundary(&self, count: u16) -> bool { self.remaining_bytes() >= count }
     /// Peek a single byte without moving cursor.
     fn peek_byte(&self) -> Result<u8, CodeEofError>;
 
@@ -40,6 +40,13 @@ impl<Id: SiteId> Bytecode<Id> for Instr<Id> {
         }
     }
 
+    fn external_ref(&self) -> Option<Id> {
+        match self {
+            Instr::Ctrl(instr) => instr.external_ref(),
+            Instr::Reserved(instr) => Bytecode::<Id>::external_ref(instr),
+        }
+    }
+
     fn encode_operands<W>(&self, writer: &mut W) -> Result<(), W::Error>
     where W: BytecodeWrite<Id> {
         match self {
@@ -83,7 +90,7 @@ impl<Id: SiteId> Bytecode<Id> for ReservedInstr {
 
 impl<Id: SiteId> CtrlInstr<Id> {
     const START: u8 = 0;
-    const END: u8 = Self::START + Self::STOP;
+    const END: u8 = Self::START + Self::BRK;
 
     const NOP: u8 = 0;
     const NOCO: u8 = 1;
@@ -101,6 +108,17 @@ impl<Id: SiteId> CtrlInstr<Id> {
     const CALL: u8 = 13;
     const RET: u8 = 14;
     const STOP: u8 = 15;
+    const BRK: u8 = 16;
+
+    /// Number of bytes taken by the operands of an instruction with the given opcode.
+    pub(super) const fn operands_len(opcode: u8) -> u16 {
+        match opcode {
+            Self::JMP | Self::JINE | Self::JIFAIL | Self::FN => 2,
+            Self::SH | Self::SHNE | Self::SHFAIL | Self::BRK => 1,
+            Self::EXEC | Self::CALL => 3,
+            _ => 0,
+        }
+    }
 }
 
 impl<Id: SiteId> Bytecode<Id> for CtrlInstr<Id> {
@@ -124,6 +142,14 @@ impl<Id: SiteId> Bytecode<Id> for CtrlInstr<Id> {
             CtrlInstr::Call { .. } => Self::CALL,
             CtrlInstr::Ret => Self::RET,
             CtrlInstr::Stop => Self::STOP,
+            CtrlInstr::Breakpoint { .. } => Self::BRK,
+        }
+    }
+
+    fn external_ref(&self) -> Option<Id> {
+        match *self {
+            CtrlInstr::Exec { site } | CtrlInstr::Call { site } => Some(site.prog_id),
+            _ => None,
         }
     }
 
@@ -145,6 +171,7 @@ impl<Id: SiteId> Bytecode<Id> for CtrlInstr<Id> {
             CtrlInstr::Sh { shift } | CtrlInstr::ShNe { shift } | CtrlInstr::ShFail { shift } => {
                 writer.write_byte(shift.to_le_bytes()[0])?
             }
+            CtrlInstr::Breakpoint { id } => writer.write_byte(id)?,
             CtrlInstr::Call { site } | CtrlInstr::Exec { site } => {
                 let site = Site::new(site.prog_id, site.offset);
                 writer.write_ref(site.prog_id)?;
@@ -159,6 +186,9 @@ impl<Id: SiteId> Bytecode<Id> for CtrlInstr<Id> {
         Self: Sized,
         R: BytecodeRead<Id>,
     {
+        if !reader.is_at_boundary(Self::operands_len(opcode)) {
+            return Err(CodeEofError);
+        }
         Ok(match opcode {
             Self::NOP => Self::Nop,
             Self::CHK => Self::Chk,
@@ -176,6 +206,7 @@ impl<Id: SiteId> Bytecode<Id> for CtrlInstr<Id> {
             Self::SH => CtrlInstr::Sh { shift: i8::from_le_bytes([reader.read_byte()?]) },
             Self::SHNE => CtrlInstr::ShNe { shift: i8::from_le_bytes([reader.read_byte()?]) },
             Self::SHFAIL => CtrlInstr::ShFail { shift: i8::from_le_bytes([reader.read_byte()?]) },
+            Self::BRK => CtrlInstr::Breakpoint { id: reader.read_byte()? },
 
             Self::CALL => {
                 let prog_id = reader.read_ref()?;
@@ -276,8 +307,57 @@ mod test {
         ]);
     }
 
+    #[test]
+    fn truncated() {
+        let lib_id = LibId::from_str(LIB_ID).unwrap();
+        let site = Site::new(lib_id, 0x69AB);
+        let mut libs = LibsSeg::new();
+        libs.push(lib_id).unwrap();
+        for instr in [
+            CtrlInstr::Jmp { pos: 0x75AE },
+            CtrlInstr::JiNe { pos: 0x75AE },
+            CtrlInstr::JiFail { pos: 0x75AE },
+            CtrlInstr::Sh { shift: -0x5 },
+            CtrlInstr::ShNe { shift: -0x5 },
+            CtrlInstr::ShFail { shift: -0x5 },
+            CtrlInstr::Exec { site },
+            CtrlInstr::Fn { pos: 0x75AE },
+            CtrlInstr::Call { site },
+            CtrlInstr::Breakpoint { id: 0x5A },
+        ] {
+            let mut marshaller = Marshaller::new(&libs);
+            instr.encode_instr(&mut marshaller).unwrap();
+            let (code, data) = marshaller.finish();
+            for len in 1..code.len() {
+                let mut marshaller = Marshaller::with(&code[..len], &data, &libs);
+                assert_eq!(
+                    Instr::<LibId>::decode_instr(&mut marshaller),
+                    Err(CodeEofError),
+                    "{instr} truncated to {len} bytes"
+                );
+            }
+        }
+    }
+
     #[test]
     fn ret() { roundtrip(CtrlInstr::Ret, [CtrlInstr::<LibId>::RET]); }
     #[test]
     fn stop() { roundtrip(CtrlInstr::Stop, [CtrlInstr::<LibId>::STOP]); }
+    #[test]
+    fn brk() { roundtrip(CtrlInstr::Breakpoint { id: 0x5A }, [CtrlInstr::<LibId>::BRK, 0x5A]); }
+    #[test]
+    fn brk_opcode() {
+        let libs = LibsSeg::new();
+        let data = SmallBlob::new();
+        let code = [0x10, 0x5A, 0x11];
+        let mut marshaller = Marshaller::with(&code, &data, &libs);
+        assert_eq!(
+            Instr::<LibId>::decode_instr(&mut marshaller),
+            Ok(Instr::Ctrl(CtrlInstr::Breakpoint { id: 0x5A }))
+        );
+        assert_eq!(
+            Instr::<LibId>::decode_instr(&mut marshaller),
+            Ok(Instr::Reserved(ReservedInstr(0x11)))
+        );
+    }
 }
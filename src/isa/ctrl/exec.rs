@@ -26,7 +26,8 @@ use alloc::collections::BTreeSet;
 
 use super::CtrlInstr;
 use crate::core::{Core, NoExt, NoRegs, Site, SiteId, Status};
-use crate::isa::{ExecStep, Instr, Instruction, ReservedInstr};
+use crate::isa::{Bytecode, ExecStep, Instr, Instruction, ReservedInstr, ISA_DEBUG};
+use crate::IsaId;
 
 impl<Id: SiteId> Instruction<Id> for Instr<Id> {
     const ISA_EXT: &'static [&'static str] = &[];
@@ -34,6 +35,66 @@ impl<Id: SiteId> Instruction<Id> for Instr<Id> {
     type Core = NoExt;
     type Context<'ctx> = ();
 
+    fn used_isae_ids(&self) -> BTreeSet<IsaId> {
+        match self {
+            Instr::Ctrl(instr) => instr.used_isae_ids(),
+            Instr::Reserved(instr) => Instruction::<Id>::used_isae_ids(instr),
+        }
+    }
+
+    fn call_sites(&self) -> impl Iterator<Item = Site<Id>> {
+        match self {
+            Instr::Ctrl(instr) => Some(instr.call_sites()),
+            Instr::Reserved(_) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    fn local_goto_pos(&mut self) -> Option<&mut u16> {
+        match self {
+            Instr::Ctrl(instr) => instr.local_goto_pos(),
+            Instr::Reserved(_) => None,
+        }
+    }
+
+    fn remote_goto_pos(&mut self) -> Option<&mut Site<Id>> {
+        match self {
+            Instr::Ctrl(instr) => instr.remote_goto_pos(),
+            Instr::Reserved(_) => None,
+        }
+    }
+
+    fn successors(&self, pos: u16, next: u16) -> impl Iterator<Item = u16> {
+        match self {
+            Instr::Ctrl(instr) => Some(instr.successors(pos, next)),
+            Instr::Reserved(_) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    fn local_call_pos(&self) -> Option<u16> {
+        match self {
+            Instr::Ctrl(instr) => Instruction::<Id>::local_call_pos(instr),
+            Instr::Reserved(_) => None,
+        }
+    }
+
+    fn is_return(&self) -> bool {
+        match self {
+            Instr::Ctrl(instr) => Instruction::<Id>::is_return(instr),
+            Instr::Reserved(_) => false,
+        }
+    }
+
+    fn breakpoint_id(&self) -> Option<u8> {
+        match self {
+            Instr::Ctrl(instr) => Instruction::<Id>::breakpoint_id(instr),
+            Instr::Reserved(_) => None,
+        }
+    }
+
     fn src_regs(&self) -> BTreeSet<NoRegs> {
         match self {
             Instr::Ctrl(instr) => instr.src_regs(),
@@ -107,6 +168,69 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
     type Core = NoExt;
     type Context<'ctx> = ();
 
+    fn used_isae_ids(&self) -> BTreeSet<IsaId> {
+        match self {
+            CtrlInstr::Breakpoint { .. } => bset![IsaId::from(ISA_DEBUG)],
+            _ => none!(),
+        }
+    }
+
+    fn call_sites(&self) -> impl Iterator<Item = Site<Id>> {
+        match *self {
+            CtrlInstr::Exec { site } | CtrlInstr::Call { site } => Some(site),
+            _ => None,
+        }
+        .into_iter()
+    }
+
+    fn local_goto_pos(&mut self) -> Option<&mut u16> {
+        match self {
+            CtrlInstr::Jmp { pos }
+            | CtrlInstr::JiNe { pos }
+            | CtrlInstr::JiFail { pos }
+            | CtrlInstr::Fn { pos } => Some(pos),
+            _ => None,
+        }
+    }
+
+    fn remote_goto_pos(&mut self) -> Option<&mut Site<Id>> {
+        match self {
+            CtrlInstr::Exec { site } | CtrlInstr::Call { site } => Some(site),
+            _ => None,
+        }
+    }
+
+    fn successors(&self, pos: u16, next: u16) -> impl Iterator<Item = u16> {
+        let shift = |shift: i8| pos.checked_add_signed(shift as i16);
+        let (first, second) = match *self {
+            CtrlInstr::Jmp { pos } => (Some(pos), None),
+            CtrlInstr::JiNe { pos } | CtrlInstr::JiFail { pos } | CtrlInstr::Fn { pos } => {
+                (Some(pos), Some(next))
+            }
+            CtrlInstr::Sh { shift: s } => (shift(s), None),
+            CtrlInstr::ShNe { shift: s } | CtrlInstr::ShFail { shift: s } => (shift(s), Some(next)),
+            CtrlInstr::Exec { .. } | CtrlInstr::Ret | CtrlInstr::Stop => (None, None),
+            _ => (Some(next), None),
+        };
+        first.into_iter().chain(second)
+    }
+
+    fn local_call_pos(&self) -> Option<u16> {
+        match *self {
+            CtrlInstr::Fn { pos } => Some(pos),
+            _ => None,
+        }
+    }
+
+    fn is_return(&self) -> bool { matches!(self, CtrlInstr::Ret) }
+
+    fn breakpoint_id(&self) -> Option<u8> {
+        match *self {
+            CtrlInstr::Breakpoint { id } => Some(id),
+            _ => None,
+        }
+    }
+
     fn src_regs(&self) -> BTreeSet<NoRegs> { none!() }
 
     fn dst_regs(&self) -> BTreeSet<NoRegs> { none!() }
@@ -120,6 +244,7 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
             | CtrlInstr::RsetCk => 0,
             CtrlInstr::Jmp { .. } | CtrlInstr::JiNe { .. } | CtrlInstr::JiFail { .. } => 2,
             CtrlInstr::Sh { .. } | CtrlInstr::ShNe { .. } | CtrlInstr::ShFail { .. } => 1,
+            CtrlInstr::Breakpoint { .. } => 1,
             CtrlInstr::Exec { .. } => 2,
             CtrlInstr::Fn { .. } => 2,
             CtrlInstr::Call { .. } => 2,
@@ -136,6 +261,7 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
             | CtrlInstr::RsetCk => 0,
             CtrlInstr::Jmp { .. } | CtrlInstr::JiNe { .. } | CtrlInstr::JiFail { .. } => 0,
             CtrlInstr::Sh { .. } | CtrlInstr::ShNe { .. } | CtrlInstr::ShFail { .. } => 0,
+            CtrlInstr::Breakpoint { .. } => 0,
             CtrlInstr::Exec { .. } => 32,
             CtrlInstr::Fn { .. } => 0,
             CtrlInstr::Call { .. } => 32,
@@ -149,6 +275,13 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
         core: &mut Core<Id, Self::Core>,
         _: &Self::Context<'_>,
     ) -> ExecStep<Site<Id>> {
+        let ret_site = || {
+            let len = 1 + Self::operands_len(self.opcode_byte());
+            current
+                .offset
+                .checked_add(len)
+                .map(|offset| Site::new(current.prog_id, offset))
+        };
         let shift_jump = |shift: i8| {
             let Some(pos) = current.offset.checked_add_signed(shift as i16) else {
                 return ExecStep::FailHalt;
@@ -199,13 +332,13 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
             }
             CtrlInstr::Exec { site } => return ExecStep::Call(site),
             CtrlInstr::Fn { pos } => {
-                return match core.push_cs(current) {
+                return match ret_site().and_then(|site| core.push_cs(site)) {
                     Some(_) => ExecStep::Jump(pos),
                     None => ExecStep::FailHalt,
                 }
             }
             CtrlInstr::Call { site } => {
-                return match core.push_cs(current) {
+                return match ret_site().and_then(|ret| core.push_cs(ret)) {
                     Some(_) => ExecStep::Call(site),
                     None => ExecStep::FailHalt,
                 }
@@ -217,7 +350,98 @@ impl<Id: SiteId> Instruction<Id> for CtrlInstr<Id> {
                 }
             }
             CtrlInstr::Stop => return ExecStep::Stop,
+            CtrlInstr::Breakpoint { .. } => {}
         }
         ExecStep::Next
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::test_utils::TestInstr;
+    use crate::{Lib, LibId};
+
+    #[test]
+    fn call_sites() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let site = Site::new(lib_id, 0x69AB);
+
+        let call = Instr::<LibId>::Ctrl(CtrlInstr::Call { site });
+        assert_eq!(call.call_sites().collect::<Vec<_>>(), vec![site]);
+        let exec = Instr::<LibId>::Ctrl(CtrlInstr::Exec { site });
+        assert_eq!(exec.call_sites().collect::<Vec<_>>(), vec![site]);
+        let func = Instr::<LibId>::Ctrl(CtrlInstr::Fn { pos: 0x69AB });
+        assert_eq!(func.call_sites().count(), 0);
+        let reserved = Instr::<LibId>::Reserved(ReservedInstr(0x80));
+        assert_eq!(reserved.call_sites().count(), 0);
+
+        let lib = Lib::assemble(&[Instr::Ctrl(CtrlInstr::Nop), call, exec]).unwrap();
+        assert_eq!(lib.dependency_ids().collect::<Vec<_>>(), vec![lib_id]);
+        let sites = lib
+            .disassemble::<Instr<LibId>>()
+            .unwrap()
+            .iter()
+            .flat_map(Instr::call_sites)
+            .collect::<Vec<_>>();
+        assert_eq!(sites, vec![site, site]);
+    }
+
+    #[test]
+    fn successors() {
+        let succ = |instr: CtrlInstr<LibId>| instr.successors(0x10, 0x13).collect::<Vec<_>>();
+        assert_eq!(succ(CtrlInstr::Nop), vec![0x13]);
+        assert_eq!(succ(CtrlInstr::Jmp { pos: 0x40 }), vec![0x40]);
+        assert_eq!(succ(CtrlInstr::JiNe { pos: 0x40 }), vec![0x40, 0x13]);
+        assert_eq!(succ(CtrlInstr::Fn { pos: 0x40 }), vec![0x40, 0x13]);
+        assert_eq!(succ(CtrlInstr::Sh { shift: -0x10 }), vec![0x00]);
+        assert_eq!(succ(CtrlInstr::ShFail { shift: -0x11 }), vec![0x13]);
+        assert_eq!(succ(CtrlInstr::Exec { site: Site::new(LibId::default(), 0) }), vec![]);
+        assert_eq!(succ(CtrlInstr::Call { site: Site::new(LibId::default(), 0) }), vec![0x13]);
+        assert_eq!(succ(CtrlInstr::Ret), vec![]);
+        assert_eq!(succ(CtrlInstr::Stop), vec![]);
+        let reserved = Instr::<LibId>::Reserved(ReservedInstr(0x80));
+        assert_eq!(reserved.successors(0x10, 0x11).count(), 0);
+    }
+
+    #[test]
+    fn total_complexity() {
+        let site = Site::new(LibId::default(), 0);
+        let code = [
+            Instr::<LibId>::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+            Instr::Ctrl(CtrlInstr::Call { site }),
+        ];
+        let total = code.iter().map(Instruction::complexity).sum::<u64>();
+        assert!(total > 0);
+        assert_eq!(Instr::total_complexity(&code), total);
+        assert_eq!(Instr::<LibId>::total_complexity(&[]), 0);
+        assert!(!Instr::exceeds_budget(&code, total));
+        assert!(Instr::exceeds_budget(&code, total - 1));
+        assert!(!Instr::<LibId>::exceeds_budget(&[], 0));
+
+        let heavy = TestInstr::Heavy;
+        assert_eq!(TestInstr::total_complexity(&[heavy]), u64::MAX / 2 + 1);
+        assert_eq!(TestInstr::total_complexity(&[heavy, heavy, heavy]), u64::MAX);
+        assert!(!TestInstr::exceeds_budget(&[heavy, heavy, heavy], u64::MAX));
+        assert!(TestInstr::exceeds_budget(&[heavy, heavy], u64::MAX - 1));
+    }
+
+    #[test]
+    fn used_isae_ids() {
+        let site = Site::new(LibId::default(), 0);
+        for instr in [
+            Instr::<LibId>::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+            Instr::Ctrl(CtrlInstr::Call { site }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Reserved(ReservedInstr(0x80)),
+        ] {
+            assert!(instr.used_isae_ids().is_empty(), "{instr}");
+        }
+        assert_eq!(Instr::<LibId>::Ctrl(CtrlInstr::Breakpoint { id: 1 }).used_isae_ids(), bset![
+            IsaId::from(ISA_DEBUG)
+        ]);
+        assert_eq!(TestInstr::Heavy.used_isae_ids(), bset![IsaId::from("TEST")]);
+    }
+}
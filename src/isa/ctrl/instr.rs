@@ -92,4 +92,12 @@ pub enum CtrlInstr<Id: SiteId> {
     /// Stop the program.
     #[display("stop")]
     Stop,
+
+    /// Software breakpoint with a user-defined id.
+    ///
+    /// Acts as no-operation unless executed with [`crate::Vm::exec_with_breakpoints`]. Requires
+    /// the library to declare the [`crate::ISA_DEBUG`] extension; otherwise halts as a
+    /// reserved instruction.
+    #[display("brk     {id}")]
+    Breakpoint { id: u8 },
 }
@@ -66,6 +66,44 @@ pub trait Instruction<Id: SiteId>: Display + Debug + Bytecode<Id> {
         TinyOrdSet::from_iter_checked(iter)
     }
 
+    /// Returns ISA extensions required by this specific instruction, which may be a subset of the
+    /// ISA extensions of the whole instruction set.
+    fn used_isae_ids(&self) -> BTreeSet<IsaId> { Self::isa_ext().into_iter().collect() }
+
+    /// Returns all external code locations which are referenced by the instruction (used in
+    /// calls and jumps into other programs).
+    fn call_sites(&self) -> impl Iterator<Item = Site<Id>> { core::iter::empty() }
+
+    /// Returns a mutable reference to the offset inside the same program referenced by the
+    /// instruction as a jump or call target, if any.
+    fn local_goto_pos(&mut self) -> Option<&mut u16> { None }
+
+    /// Returns a mutable reference to the external code location referenced by the instruction,
+    /// if any.
+    fn remote_goto_pos(&mut self) -> Option<&mut Site<Id>> { None }
+
+    /// Returns offsets inside the same program of all instructions which may be executed right
+    /// after this instruction, located at offset `pos`. The `next` argument is the offset of the
+    /// following instruction.
+    ///
+    /// Used in static code analysis; an instruction which stops the program or passes the control
+    /// to other program without returning must return an empty iterator.
+    fn successors(&self, pos: u16, next: u16) -> impl Iterator<Item = u16> {
+        let _ = pos;
+        core::iter::once(next)
+    }
+
+    /// Returns offset of a subroutine inside the same program called by the instruction, if the
+    /// control returns to the next instruction once the subroutine completes.
+    fn local_call_pos(&self) -> Option<u16> { None }
+
+    /// Returns whether the instruction returns the control to the instruction following the call
+    /// site of the current subroutine.
+    fn is_return(&self) -> bool { false }
+
+    /// Returns id of a software breakpoint, if the instruction is a breakpoint.
+    fn breakpoint_id(&self) -> Option<u8> { None }
+
     /// Lists all registers which are used by the instruction.
     fn regs(&self) -> BTreeSet<<Self::Core as CoreExt>::Reg> {
         let mut regs = self.src_regs();
@@ -118,6 +156,25 @@ pub trait Instruction<Id: SiteId>: Display + Debug + Bytecode<Id> {
     /// Computational complexity is the number of "CPU ticks" required to process the instruction.
     fn complexity(&self) -> u64 { self.base_complexity() }
 
+    /// Returns total computational complexity of a straight-line sequence of instructions,
+    /// saturating at `u64::MAX`.
+    fn total_complexity(code: &[Self]) -> u64
+    where Self: Sized {
+        code.iter()
+            .fold(0u64, |acc, instr| acc.saturating_add(instr.complexity()))
+    }
+
+    /// Checks whether total computational complexity of a straight-line sequence of instructions
+    /// exceeds the `budget`, stopping at the first instruction which exceeds it.
+    fn exceeds_budget(code: &[Self], budget: u64) -> bool
+    where Self: Sized {
+        let mut acc = 0u64;
+        code.iter().any(|instr| {
+            acc = acc.saturating_add(instr.complexity());
+            acc > budget
+        })
+    }
+
     /// Executes given instruction taking all registers as input and output.
     ///
     /// # Arguments
@@ -169,6 +169,9 @@ macro_rules! instr {
     (stop) => {
         Instr::Ctrl(CtrlInstr::Stop)
     };
+    (brk $id:literal) => {
+        Instr::Ctrl(CtrlInstr::Breakpoint { id: $id })
+    };
 
     // Jumps
     (jmp $pos:literal) => {
@@ -30,8 +30,10 @@ mod arch;
 
 mod ctrl;
 mod masm;
+#[cfg(test)]
+pub(crate) mod test_utils;
 
-pub use arch::{Instr, IsaId, ReservedInstr, ISA_ID_MAX_LEN};
+pub use arch::{Instr, IsaId, ReservedInstr, ISA_DEBUG, ISA_ID_MAX_LEN};
 pub use bytecode::{Bytecode, BytecodeRead, BytecodeWrite, CodeEofError};
 pub use ctrl::CtrlInstr;
 pub use instr::{ExecStep, Instruction};
@@ -0,0 +1,259 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+//! Instruction set and core extension shared by the unit tests.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::fmt::{self, Display, Formatter};
+use core::ops::RangeInclusive;
+
+use super::{
+    Bytecode, BytecodeRead, BytecodeWrite, CodeEofError, CtrlInstr, ExecStep, Instruction,
+};
+use crate::core::{Core, CoreExt, NoExt, Register, Site};
+use crate::LibId;
+
+/// Register of the [`Regs`] core extension.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display("R{0}")]
+pub struct Reg(pub u8);
+
+impl Register for Reg {
+    type Value = u64;
+    fn bytes(self) -> u16 { 8 }
+}
+
+/// Core extension with a set of [`Reg`] registers.
+#[derive(Clone, Debug, Default)]
+pub struct Regs(BTreeMap<Reg, u64>);
+
+impl CoreExt for Regs {
+    type Reg = Reg;
+    type Config = ();
+
+    fn with(_: Self::Config) -> Self { Regs::default() }
+    fn get(&self, reg: Reg) -> Option<u64> { self.0.get(&reg).copied() }
+    fn clr(&mut self, reg: Reg) -> Option<u64> { self.0.remove(&reg) }
+    fn set(&mut self, reg: Reg, val: u64) -> Option<u64> { self.0.insert(reg, val) }
+    fn reset(&mut self) { self.0.clear() }
+}
+
+/// Instruction set extending control flow instructions with register instructions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TestInstr {
+    Ctrl(CtrlInstr<LibId>),
+    /// Puts a constant, stored in the data segment, into a register.
+    Const {
+        reg: u8,
+        val: u32,
+    },
+    /// No-operation with complexity large enough to saturate the complexity accumulator.
+    Heavy,
+    /// Loads the value provided by the execution context into a register.
+    Load(u8),
+    /// Reads a register without changing any state.
+    Read(u8),
+}
+
+impl TestInstr {
+    const CONST: u8 = 0x80;
+    const HEAVY: u8 = 0x81;
+    const LOAD: u8 = 0x82;
+    const READ: u8 = 0x83;
+}
+
+impl Display for TestInstr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TestInstr::Ctrl(instr) => Display::fmt(instr, f),
+            TestInstr::Const { reg, val } => write!(f, "const   {}, {val:#X}", Reg(*reg)),
+            TestInstr::Heavy => f.write_str("heavy"),
+            TestInstr::Load(reg) => write!(f, "ld      {}, :context", Reg(*reg)),
+            TestInstr::Read(reg) => write!(f, "read    {}", Reg(*reg)),
+        }
+    }
+}
+
+impl Bytecode<LibId> for TestInstr {
+    fn op_range() -> RangeInclusive<u8> { 0..=Self::READ }
+
+    fn opcode_byte(&self) -> u8 {
+        match self {
+            TestInstr::Ctrl(instr) => instr.opcode_byte(),
+            TestInstr::Const { .. } => Self::CONST,
+            TestInstr::Heavy => Self::HEAVY,
+            TestInstr::Load(_) => Self::LOAD,
+            TestInstr::Read(_) => Self::READ,
+        }
+    }
+
+    fn encode_operands<W>(&self, writer: &mut W) -> Result<(), W::Error>
+    where W: BytecodeWrite<LibId> {
+        match self {
+            TestInstr::Ctrl(instr) => instr.encode_operands(writer),
+            TestInstr::Const { reg, val } => {
+                writer.write_byte(*reg)?;
+                writer.write_fixed(val.to_le_bytes())
+            }
+            TestInstr::Heavy => Ok(()),
+            TestInstr::Load(reg) | TestInstr::Read(reg) => writer.write_byte(*reg),
+        }
+    }
+
+    fn decode_operands<R>(reader: &mut R, opcode: u8) -> Result<Self, CodeEofError>
+    where R: BytecodeRead<LibId> {
+        match opcode {
+            Self::CONST => {
+                let reg = reader.read_byte()?;
+                let val = reader.read_fixed(u32::from_le_bytes)?;
+                Ok(TestInstr::Const { reg, val })
+            }
+            Self::HEAVY => Ok(TestInstr::Heavy),
+            Self::LOAD => reader.read_byte().map(TestInstr::Load),
+            Self::READ => reader.read_byte().map(TestInstr::Read),
+            _ => CtrlInstr::decode_operands(reader, opcode).map(TestInstr::Ctrl),
+        }
+    }
+}
+
+impl Instruction<LibId> for TestInstr {
+    const ISA_EXT: &'static [&'static str] = &["TEST"];
+    type Core = Regs;
+    type Context<'ctx> = u64;
+
+    fn call_sites(&self) -> impl Iterator<Item = Site<LibId>> {
+        match self {
+            TestInstr::Ctrl(instr) => Some(instr.call_sites()),
+            _ => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    fn local_goto_pos(&mut self) -> Option<&mut u16> {
+        match self {
+            TestInstr::Ctrl(instr) => instr.local_goto_pos(),
+            _ => None,
+        }
+    }
+
+    fn remote_goto_pos(&mut self) -> Option<&mut Site<LibId>> {
+        match self {
+            TestInstr::Ctrl(instr) => instr.remote_goto_pos(),
+            _ => None,
+        }
+    }
+
+    fn successors(&self, pos: u16, next: u16) -> impl Iterator<Item = u16> {
+        let succ = match self {
+            TestInstr::Ctrl(instr) => instr.successors(pos, next).collect(),
+            _ => vec![next],
+        };
+        succ.into_iter()
+    }
+
+    fn local_call_pos(&self) -> Option<u16> {
+        match self {
+            TestInstr::Ctrl(instr) => Instruction::<LibId>::local_call_pos(instr),
+            _ => None,
+        }
+    }
+
+    fn is_return(&self) -> bool {
+        match self {
+            TestInstr::Ctrl(instr) => Instruction::<LibId>::is_return(instr),
+            _ => false,
+        }
+    }
+
+    fn breakpoint_id(&self) -> Option<u8> {
+        match self {
+            TestInstr::Ctrl(instr) => Instruction::<LibId>::breakpoint_id(instr),
+            _ => None,
+        }
+    }
+
+    fn src_regs(&self) -> BTreeSet<Reg> {
+        match self {
+            TestInstr::Read(reg) => bset![Reg(*reg)],
+            _ => none!(),
+        }
+    }
+
+    fn dst_regs(&self) -> BTreeSet<Reg> {
+        match self {
+            TestInstr::Ctrl(_) | TestInstr::Heavy | TestInstr::Read(_) => none!(),
+            TestInstr::Const { reg, .. } | TestInstr::Load(reg) => bset![Reg(*reg)],
+        }
+    }
+
+    fn op_data_bytes(&self) -> u16 {
+        match self {
+            TestInstr::Ctrl(instr) => Instruction::<LibId>::op_data_bytes(instr),
+            TestInstr::Const { .. } => 5,
+            TestInstr::Heavy => 0,
+            TestInstr::Load(_) | TestInstr::Read(_) => 1,
+        }
+    }
+
+    fn ext_data_bytes(&self) -> u16 {
+        match self {
+            TestInstr::Ctrl(instr) => Instruction::<LibId>::ext_data_bytes(instr),
+            TestInstr::Const { .. } | TestInstr::Heavy | TestInstr::Read(_) => 0,
+            TestInstr::Load(_) => 8,
+        }
+    }
+
+    fn complexity(&self) -> u64 {
+        match self {
+            TestInstr::Heavy => u64::MAX / 2 + 1,
+            _ => self.base_complexity(),
+        }
+    }
+
+    fn exec(
+        &self,
+        site: Site<LibId>,
+        core: &mut Core<LibId, Regs>,
+        context: &Self::Context<'_>,
+    ) -> ExecStep<Site<LibId>> {
+        match self {
+            TestInstr::Ctrl(instr) => {
+                let cx = core.cx.clone();
+                let mut ctrl = core.clone().extend(NoExt);
+                let step = instr.exec(site, &mut ctrl, &());
+                *core = ctrl.extend(cx);
+                return step;
+            }
+            TestInstr::Const { reg, val } => {
+                core.cx.set(Reg(*reg), *val as u64);
+            }
+            TestInstr::Heavy | TestInstr::Read(_) => {}
+            TestInstr::Load(reg) => {
+                core.cx.set(Reg(*reg), *context);
+            }
+        }
+        ExecStep::Next
+    }
+}
@@ -160,14 +160,24 @@ pub mod regs {
     pub use crate::core::{Status, CALL_STACK_SIZE_MAX};
 }
 
-pub use isa::{ExecStep, IsaId, ISA_ID_MAX_LEN};
+pub use isa::{ExecStep, IsaId, ISA_DEBUG, ISA_ID_MAX_LEN};
 #[cfg(feature = "armor")]
 pub use library::armor::LibArmorError;
-pub use library::{Lib, LibId, LibSite};
+pub use library::{
+    AssemblerError, ComplexityAnalysisError, ComplexityProportionalGasModel, ConstantGasModel,
+    DataEntry, DecryptError, DepError, EncryptedLib, GasModel, InlineError, InstrChange, InstrIter,
+    InterferenceGraph, IsaeCompatError, JumpTargetError, Lib, LibDiff, LibId, LibSite,
+    LibsSegOverflow, LivenessMap, ValidationError,
+};
 #[doc(hidden)]
 pub use paste::paste;
-pub use vm::Vm;
+#[cfg(feature = "audit")]
+pub use vm::{AuditEntry, AuditLog};
+pub use vm::{BenchStats, BreakpointAction, BreakpointHandler, CycleGuard, Vm};
 
-pub use self::core::{Core, CoreConfig, CoreExt, NoExt, NoRegs, Register, Site, SiteId};
+pub use self::core::{
+    Core, CoreConfig, CoreExt, InitError, NoExt, NoRegs, Register, RegisterChange, RegisterDiff,
+    RegisterSnapshot, Site, SiteId,
+};
 
 pub const LIB_NAME_ALUVM: &str = "AluVM";
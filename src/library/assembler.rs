@@ -22,10 +22,15 @@
 // or implied. See the License for the specific language governing permissions and limitations under
 // the License.
 
-use amplify::confinement::{self, TinyOrdSet};
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::fmt::Write;
+use core::marker::PhantomData;
 
-use super::{Lib, LibId, MarshallError, Marshaller};
-use crate::isa::{BytecodeRead, CodeEofError, Instruction};
+use amplify::confinement::{self, SmallBlob, TinyOrdSet};
+
+use super::{Lib, LibId, LibsSeg, MarshallError, Marshaller};
+use crate::isa::{BytecodeRead, CodeEofError, Instruction, ISA_DEBUG};
+use crate::IsaId;
 
 /// Errors while assembling lib-old from the instruction set.
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Display, Error, From)]
@@ -35,13 +40,133 @@ pub enum AssemblerError {
     #[from]
     Bytecode(MarshallError),
 
-    /// Error assembling library segment.
+    /// Error assembling library or ISA extensions segment.
     #[from]
     LibSegOverflow(confinement::Error),
 }
 
+/// Errors while validating library bytecode.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ValidationError {
+    /// library requires ISA extension {0} which is not supported by the instruction set.
+    UnsupportedIsae(IsaId),
+
+    /// incomplete instruction at offset {0:#06X}.
+    Incomplete(u16),
+
+    /// instruction at offset {0:#06X} has a non-canonical encoding.
+    NonCanonical(u16),
+
+    /// instruction at offset {0:#06X} references a library absent from the library segment.
+    UnknownLib(u16),
+}
+
+/// Iterator over the instructions of a library code, created with [`Lib::iter_instructions`].
+///
+/// Yields offset of each instruction in the code segment together with the decoded instruction.
+/// Stops after the first decoding error.
+pub struct InstrIter<'lib, Isa>
+where Isa: Instruction<LibId>
+{
+    reader: Marshaller<'lib, &'lib [u8], &'lib [u8]>,
+    failed: bool,
+    phantom: PhantomData<fn() -> Isa>,
+}
+
+impl<Isa> Iterator for InstrIter<'_, Isa>
+where Isa: Instruction<LibId>
+{
+    type Item = Result<(u16, Isa), CodeEofError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.reader.is_eof() {
+            return None;
+        }
+        let pos = self.reader.pos();
+        let res = Isa::decode_instr(&mut self.reader).map(|instr| (pos, instr));
+        if res.is_err() {
+            self.failed = true;
+            let _ = self.reader.seek(pos);
+        }
+        Some(res)
+    }
+}
+
+impl<Isa> InstrIter<'_, Isa>
+where Isa: Instruction<LibId>
+{
+    /// Returns offset of the instruction which is decoded next, thus after decoding an instruction
+    /// the offset of the instruction following it.
+    ///
+    /// After a decoding error returns offset of the instruction which has failed to decode.
+    pub fn pos(&self) -> u16 { self.reader.pos() }
+}
+
 impl Lib {
+    /// Constructs library from the raw segments, verifying that the code segment is a valid
+    /// bytecode for the instruction set `Isa`.
+    ///
+    /// The validation ensures that all ISA extensions used by the library are supported by the
+    /// instruction set (with the exception of [`ISA_DEBUG`], which is always supported), that the
+    /// code is decoded into a sequence of instructions, which encode back into exactly the same
+    /// code segment, and that all libraries called by the code are present in the library
+    /// segment.
+    pub fn from_bytecode_validated<Isa>(
+        isae: TinyOrdSet<IsaId>,
+        code: SmallBlob,
+        data: SmallBlob,
+        libs: LibsSeg,
+    ) -> Result<Lib, ValidationError>
+    where
+        Isa: Instruction<LibId>,
+    {
+        let supported = Isa::isa_ext();
+        let debug = IsaId::from(ISA_DEBUG);
+        if let Some(isa) = isae
+            .iter()
+            .find(|isa| **isa != debug && !supported.contains(*isa))
+        {
+            return Err(ValidationError::UnsupportedIsae(isa.clone()));
+        }
+
+        let mut reader = Marshaller::with(&code, &data, &libs);
+        let mut writer = Marshaller::new(&libs);
+        let mut offsets = Vec::new();
+        while !reader.is_eof() {
+            let pos = reader.pos();
+            offsets.push(pos);
+            let instr =
+                Isa::decode_instr(&mut reader).map_err(|_| ValidationError::Incomplete(pos))?;
+            if instr.call_sites().any(|site| !libs.contains(&site.prog_id)) {
+                return Err(ValidationError::UnknownLib(pos));
+            }
+            instr
+                .encode_instr(&mut writer)
+                .map_err(|_| ValidationError::NonCanonical(pos))?;
+        }
+        let (reassembled, _) = writer.finish();
+        if let Some(pos) = code
+            .iter()
+            .zip(reassembled.iter())
+            .position(|(a, b)| a != b)
+            .or_else(|| (code.len() != reassembled.len()).then_some(code.len()))
+        {
+            let offset = offsets
+                .into_iter()
+                .take_while(|offset| *offset as usize <= pos)
+                .last()
+                .unwrap_or_default();
+            return Err(ValidationError::NonCanonical(offset));
+        }
+
+        Ok(Lib::from_bytecode_unchecked(isae, code, data, libs))
+    }
+
     /// Assembles library from the provided instructions by encoding them into bytecode.
+    ///
+    /// The library declares ISA extensions of the instruction set; [`ISA_DEBUG`] is declared only
+    /// if the code contains breakpoint instructions.
     pub fn assemble<Isa>(code: &[Isa]) -> Result<Lib, AssemblerError>
     where Isa: Instruction<LibId> {
         let call_sites = code.iter().filter_map(|instr| instr.external_ref());
@@ -53,8 +178,13 @@ impl Lib {
         }
         let (code_segment, data_segment) = writer.finish();
 
+        let mut isae = Isa::isa_ext();
+        if code.iter().any(|instr| instr.breakpoint_id().is_some()) {
+            isae.push(IsaId::from(ISA_DEBUG))?;
+        }
+
         Ok(Lib {
-            isae: Isa::isa_ext(),
+            isae,
             libs: libs_segment,
             code: code_segment,
             data: data_segment,
@@ -64,12 +194,97 @@ impl Lib {
     /// Disassembles library into a set of instructions.
     pub fn disassemble<Isa>(&self) -> Result<Vec<Isa>, CodeEofError>
     where Isa: Instruction<LibId> {
-        let mut code = Vec::new();
-        let mut reader = Marshaller::with(&self.code, &self.data, &self.libs);
-        while !reader.is_eof() {
-            code.push(Isa::decode_instr(&mut reader)?);
+        self.iter_instructions::<Isa>()
+            .map(|res| res.map(|(_, instr)| instr))
+            .collect()
+    }
+
+    /// Disassembles the library and assembles it back, checking that the resulting code segment,
+    /// data segment and ISA extensions are equal to the original ones.
+    ///
+    /// # Panics
+    ///
+    /// If the library can't be disassembled or re-assembled, or the round-trip alters the library.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn roundtrip_assert<Isa>(&self)
+    where Isa: Instruction<LibId> {
+        let code = self
+            .disassemble::<Isa>()
+            .unwrap_or_else(|_| panic!("library {} can't be disassembled", self.lib_id()));
+        let lib = Lib::assemble(&code)
+            .unwrap_or_else(|err| panic!("library {} can't be re-assembled: {err}", self.lib_id()));
+        assert_eq!(
+            lib.code,
+            self.code,
+            "code segment of library {} is altered by round-trip",
+            self.lib_id()
+        );
+        assert_eq!(
+            lib.data,
+            self.data,
+            "data segment of library {} is altered by round-trip",
+            self.lib_id()
+        );
+        assert_eq!(
+            lib.isae_string(),
+            self.isae_string(),
+            "ISA extensions of library {} are altered by round-trip",
+            self.lib_id()
+        );
+    }
+
+    /// Constructs iterator lazily decoding library code into instructions, without allocating
+    /// the whole disassembled code.
+    pub fn iter_instructions<Isa>(&self) -> InstrIter<'_, Isa>
+    where Isa: Instruction<LibId> {
+        InstrIter {
+            reader: Marshaller::with(self.code.as_slice(), self.data.as_slice(), &self.libs),
+            failed: false,
+            phantom: PhantomData,
         }
-        Ok(code)
+    }
+
+    /// Removes from the data segment all data which are not referenced by the instructions of the
+    /// code segment.
+    ///
+    /// The code segment is re-assembled, such that instructions reference the new data segment,
+    /// while ISA extensions and library segments are left unchanged.
+    pub fn strip_debug_data<Isa>(&self) -> Result<Lib, AssemblerError>
+    where Isa: Instruction<LibId> {
+        let code = self.disassemble::<Isa>().map_err(MarshallError::from)?;
+
+        let mut writer = Marshaller::new(&self.libs);
+        for instr in code.iter() {
+            instr.encode_instr(&mut writer)?;
+        }
+        let (code_segment, data_segment) = writer.finish();
+
+        Ok(Lib {
+            isae: self.isae.clone(),
+            libs: self.libs.clone(),
+            code: code_segment,
+            data: data_segment,
+        })
+    }
+
+    /// Constructs a new library where all calls into the library `old_id` are replaced with calls
+    /// into the library `new_id`.
+    ///
+    /// The code segment is re-assembled, while ISA extensions are left unchanged. Since the
+    /// library segment changes, the returned library has a different id.
+    pub fn relocate<Isa>(&self, old_id: LibId, new_id: LibId) -> Result<Lib, AssemblerError>
+    where Isa: Instruction<LibId> {
+        let mut code = self.disassemble::<Isa>().map_err(MarshallError::from)?;
+        for instr in &mut code {
+            if let Some(site) = instr.remote_goto_pos() {
+                if site.prog_id == old_id {
+                    site.prog_id = new_id;
+                }
+            }
+        }
+        let mut lib = Lib::assemble(&code)?;
+        lib.isae = self.isae.clone();
+        Ok(lib)
     }
 
     /// Disassembles library into a set of instructions and offsets and prints it to the writer.
@@ -91,4 +306,366 @@ impl Lib {
         }
         Ok(())
     }
+
+    /// Disassembles library into an assembly listing, where targets of local jumps and calls are
+    /// replaced with symbolic labels `L0`, `L1` etc., inserted before the target instructions.
+    ///
+    /// Targets which don't point to a start of some instruction are left as raw offsets. The target
+    /// is replaced only when an instruction formats it as its last operand, in the `{pos:04X}#h`
+    /// form used by the control flow instructions; otherwise the instruction is listed unchanged.
+    pub fn annotate_with_labels<Isa>(&self) -> Result<String, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let mut code = self
+            .iter_instructions::<Isa>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let offsets = code.iter().map(|(pos, _)| *pos).collect::<BTreeSet<_>>();
+        let labels = code
+            .iter_mut()
+            .filter_map(|(_, instr)| instr.local_goto_pos().copied())
+            .filter(|target| offsets.contains(target))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .enumerate()
+            .map(|(no, target)| (target, format!("L{no}")))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut listing = String::new();
+        for (pos, mut instr) in code {
+            if let Some(label) = labels.get(&pos) {
+                writeln!(listing, "{label}:").expect("strings are infallible");
+            }
+            let line = instr.to_string();
+            let labeled = instr.local_goto_pos().and_then(|target| {
+                let label = labels.get(target)?;
+                let operands = line.strip_suffix(&format!("{target:04X}#h"))?;
+                operands
+                    .ends_with([' ', ','])
+                    .then(|| format!("{operands}{label}"))
+            });
+            let line = labeled.unwrap_or(line);
+            writeln!(listing, "@x{pos:06X}: {line}").expect("strings are infallible");
+        }
+        Ok(listing)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::Status;
+    use crate::isa::test_utils::{Reg, TestInstr};
+    use crate::isa::{CtrlInstr, Instr};
+    use crate::{LibSite, Site, Vm};
+
+    #[test]
+    fn from_bytecode_validated() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(lib_id, 0x69AB) }),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let validated = Lib::from_bytecode_validated::<Instr<LibId>>(
+            lib.isae.clone(),
+            lib.code.clone(),
+            lib.data.clone(),
+            lib.libs.clone(),
+        )
+        .unwrap();
+        assert_eq!(validated, lib);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let site = Site::new(lib_id, 0x69AB);
+        let code = [
+            CtrlInstr::Nop,
+            CtrlInstr::Chk,
+            CtrlInstr::NotCo,
+            CtrlInstr::FailCk,
+            CtrlInstr::RsetCk,
+            CtrlInstr::Jmp { pos: 0 },
+            CtrlInstr::JiNe { pos: 1 },
+            CtrlInstr::JiFail { pos: 2 },
+            CtrlInstr::Sh { shift: -3 },
+            CtrlInstr::ShNe { shift: 4 },
+            CtrlInstr::ShFail { shift: 5 },
+            CtrlInstr::Exec { site },
+            CtrlInstr::Fn { pos: 6 },
+            CtrlInstr::Call { site },
+            CtrlInstr::Ret,
+            CtrlInstr::Stop,
+        ];
+        let mut code = code.into_iter().map(Instr::Ctrl).collect::<Vec<_>>();
+        let lib = Lib::assemble(&code).unwrap();
+        lib.roundtrip_assert::<Instr<LibId>>();
+
+        let mut reserved = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Nop)]).unwrap();
+        reserved.code = SmallBlob::from_checked(vec![0x00, 0x80, 0xFF]);
+        reserved.roundtrip_assert::<Instr<LibId>>();
+        code.extend(reserved.disassemble::<Instr<LibId>>().unwrap());
+        Lib::assemble(&code)
+            .unwrap()
+            .roundtrip_assert::<Instr<LibId>>();
+    }
+
+    #[test]
+    #[should_panic(expected = "data segment of library")]
+    fn roundtrip_debug_data() {
+        let mut lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Nop)]).unwrap();
+        lib.data = SmallBlob::from_checked(vec![0xDE, 0xAD]);
+        lib.roundtrip_assert::<Instr<LibId>>();
+    }
+
+    #[test]
+    fn annotate_with_labels() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let listing = lib.annotate_with_labels::<Instr<LibId>>().unwrap();
+        assert_eq!(listing, "@x000000: jif     CO, L0\n@x000003: nop\nL0:\n@x000004: stop\n");
+        assert_eq!(listing.matches("L0:").count(), 1);
+        assert_eq!(listing.matches("L0").count(), 2);
+
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos: 2 }),
+        ])
+        .unwrap();
+        let listing = lib.annotate_with_labels::<Instr<LibId>>().unwrap();
+        assert_eq!(listing, "@x000000: nop\n@x000001: jmp     0002#h\n");
+    }
+
+    #[test]
+    fn strip_debug_data() {
+        let code = [
+            TestInstr::Const { reg: 0, val: 0xDEADBEEF },
+            TestInstr::Ctrl(CtrlInstr::Nop),
+            TestInstr::Const { reg: 1, val: 0x01020304 },
+            TestInstr::Const { reg: 2, val: 0xDEADBEEF },
+            TestInstr::Ctrl(CtrlInstr::Stop),
+        ];
+        // Constant which is present in the data segment, but is not referenced by the code
+        let unused = TestInstr::Const { reg: 3, val: 0xCAFEBABE };
+        let unused_len = Lib::assemble(&[unused]).unwrap().code.len();
+        let mut lib = Lib::assemble(&[&[unused][..], &code].concat()).unwrap();
+        lib.code = SmallBlob::from_checked(lib.code[unused_len..].to_vec());
+        assert_eq!(lib.disassemble::<TestInstr>().unwrap(), code);
+        assert_eq!(lib.data.len(), 12);
+
+        let stripped = lib.strip_debug_data::<TestInstr>().unwrap();
+        assert_eq!(stripped.data.len(), 8);
+        assert_ne!(stripped.code, lib.code);
+        assert_eq!(stripped.libs, lib.libs);
+        assert_eq!(stripped.isae, lib.isae);
+        assert_eq!(stripped.disassemble::<TestInstr>().unwrap(), code);
+        let constants = |lib: &Lib| {
+            lib.extract_constants::<TestInstr>()
+                .unwrap()
+                .into_iter()
+                .map(|entry| (entry.offset, entry.value.to_vec()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(constants(&lib), vec![
+            (4, vec![0xEF, 0xBE, 0xAD, 0xDE]),
+            (8, vec![0x04, 0x03, 0x02, 0x01])
+        ]);
+        assert_eq!(constants(&stripped), vec![
+            (0, vec![0xEF, 0xBE, 0xAD, 0xDE]),
+            (4, vec![0x04, 0x03, 0x02, 0x01])
+        ]);
+
+        let exec = |lib: &Lib| {
+            let mut vm = Vm::<TestInstr>::new();
+            let status = vm.exec(LibSite::new(lib.lib_id(), 0), &0, |_| Some(lib));
+            (
+                status,
+                vm.core.co(),
+                vm.core.ca(),
+                (0..4).map(|reg| vm.core.get(Reg(reg))).collect::<Vec<_>>(),
+            )
+        };
+        let (status, co, ca, regs) = exec(&lib);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(regs, vec![Some(0xDEADBEEF), Some(0x01020304), Some(0xDEADBEEF), None]);
+        assert_eq!(exec(&stripped), (status, co, ca, regs));
+    }
+
+    #[test]
+    fn from_bytecode_unsupported_isae() {
+        let lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Nop)]).unwrap();
+        let isae = tiny_bset![IsaId::from("BPDIGEST")];
+        assert_eq!(
+            Lib::from_bytecode_validated::<Instr<LibId>>(isae, lib.code, lib.data, lib.libs),
+            Err(ValidationError::UnsupportedIsae(IsaId::from("BPDIGEST")))
+        );
+    }
+
+    #[test]
+    fn assemble_debug_isae() {
+        let lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Nop)]).unwrap();
+        assert!(lib.isae.is_empty());
+
+        let lib = Lib::assemble(&[
+            Instr::<LibId>::Ctrl(CtrlInstr::Breakpoint { id: 1 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        assert_eq!(lib.isae, tiny_bset![IsaId::from(ISA_DEBUG)]);
+        let validated = Lib::from_bytecode_validated::<Instr<LibId>>(
+            lib.isae.clone(),
+            lib.code.clone(),
+            lib.data.clone(),
+            lib.libs.clone(),
+        );
+        assert_eq!(validated, Ok(lib));
+    }
+
+    #[test]
+    fn from_bytecode_incomplete() {
+        let lib = Lib::assemble(&[
+            Instr::<LibId>::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0x75AE }),
+        ])
+        .unwrap();
+        let code = SmallBlob::from_checked(lib.code[..3].to_vec());
+        assert_eq!(
+            Lib::from_bytecode_validated::<Instr<LibId>>(lib.isae, code, lib.data, lib.libs),
+            Err(ValidationError::Incomplete(1))
+        );
+    }
+
+    #[test]
+    fn from_bytecode_absent_lib() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(lib_id, 0x69AB) }),
+        ])
+        .unwrap();
+        let mut code = lib.code.release();
+        // Reference to the second library, which is not present in the library segment
+        code[2] = 1;
+        let code = SmallBlob::from_checked(code);
+        assert_eq!(
+            Lib::from_bytecode_validated::<Instr<LibId>>(lib.isae, code, lib.data, lib.libs),
+            Err(ValidationError::UnknownLib(1))
+        );
+    }
+
+    #[test]
+    fn relocate() {
+        let old_id = LibId::from([0xA5u8; 32]);
+        let new_id = LibId::from([0x5Au8; 32]);
+        let other_id = LibId::from([0x11u8; 32]);
+        let code = [
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(old_id, 0x69AB) }),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(other_id, 0x0001) }),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(old_id, 0x0002) }),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let relocated = lib.relocate::<Instr<LibId>>(old_id, new_id).unwrap();
+        assert_ne!(relocated.lib_id(), lib.lib_id());
+        assert_eq!(relocated.isae, lib.isae);
+        assert_eq!(relocated.libs, tiny_bset![new_id, other_id]);
+        assert_eq!(relocated.disassemble::<Instr<LibId>>().unwrap(), [
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(new_id, 0x69AB) }),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(other_id, 0x0001) }),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(new_id, 0x0002) }),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ]);
+
+        let unchanged = lib.relocate::<Instr<LibId>>(new_id, old_id).unwrap();
+        assert_eq!(unchanged.lib_id(), lib.lib_id());
+    }
+
+    /// Inputs from `fuzz/regressions/disassemble`, in the format of the `disassemble` fuzz target.
+    #[test]
+    fn fuzz_regressions() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let check = |input: &[u8]| {
+            let (&split, rest) = input.split_first().unwrap();
+            let (data, code) = rest.split_at((split as usize).min(rest.len()));
+            let lib = Lib::from_bytecode_unchecked(
+                Instr::<LibId>::isa_ext(),
+                SmallBlob::from_checked(code.to_vec()),
+                SmallBlob::from_checked(data.to_vec()),
+                tiny_bset![lib_id],
+            );
+            (
+                lib.disassemble::<Instr<LibId>>(),
+                lib.to_dot::<Instr<LibId>>().is_ok(),
+                Lib::from_bytecode_validated::<Instr<LibId>>(
+                    lib.isae, lib.code, lib.data, lib.libs,
+                ),
+            )
+        };
+
+        let (code, dot, validated) =
+            check(include_bytes!("../../fuzz/regressions/disassemble/truncated-exec"));
+        assert_eq!(code, Err(CodeEofError));
+        assert!(!dot);
+        assert_eq!(validated, Err(ValidationError::Incomplete(0)));
+
+        let (code, dot, validated) =
+            check(include_bytes!("../../fuzz/regressions/disassemble/truncated-jmp"));
+        assert_eq!(code, Err(CodeEofError));
+        assert!(!dot);
+        assert_eq!(validated, Err(ValidationError::Incomplete(0)));
+
+        // The call references the sixth library of a single-library segment; disassembly can't
+        // represent it and substitutes the default id, thus validation must reject the code.
+        let (code, dot, validated) =
+            check(include_bytes!("../../fuzz/regressions/disassemble/call-outside-libs"));
+        let code = code.unwrap();
+        assert_eq!(code, vec![Instr::Ctrl(CtrlInstr::Call {
+            site: Site::new(LibId::default(), 0)
+        })]);
+        assert!(code
+            .iter()
+            .flat_map(Instr::call_sites)
+            .all(|site| site.prog_id != lib_id));
+        assert!(dot);
+        assert_eq!(validated, Err(ValidationError::UnknownLib(0)));
+    }
+
+    #[test]
+    fn iter_instructions() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(LibId::from([0xA5u8; 32]), 0x69AB) }),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let iter = lib.iter_instructions::<Instr<LibId>>();
+        assert_send(&iter);
+        let items = iter.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(items.iter().map(|(pos, _)| *pos).collect::<Vec<_>>(), vec![0, 1, 5, 8]);
+        assert_eq!(
+            items
+                .into_iter()
+                .map(|(_, instr)| instr)
+                .collect::<Vec<_>>(),
+            lib.disassemble::<Instr<LibId>>().unwrap()
+        );
+
+        let mut lib = lib;
+        lib.code = SmallBlob::from_checked(vec![0x00, 0x05, 0xFF]);
+        let mut iter = lib.iter_instructions::<Instr<LibId>>();
+        assert_eq!(iter.pos(), 0);
+        assert_eq!(iter.next(), Some(Ok((0, Instr::Ctrl(CtrlInstr::Nop)))));
+        assert_eq!(iter.pos(), 1);
+        assert_eq!(iter.next(), Some(Err(CodeEofError)));
+        assert_eq!(iter.pos(), 1);
+        assert_eq!(iter.next(), None);
+    }
 }
@@ -0,0 +1,365 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeMap;
+
+use super::validate::IncompleteInstr;
+use super::{Lib, LibId};
+use crate::core::Site;
+use crate::isa::Instruction;
+
+/// Errors happening during static analysis of the worst-case complexity of library code.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ComplexityAnalysisError {
+    /// incomplete instruction at offset {0:#06X}.
+    Incomplete(u16),
+
+    /// instruction at offset {from:#06X} jumps to offset {target:#06X}, which is not a start of
+    /// an instruction.
+    InvalidTarget { from: u16, target: u16 },
+
+    /// instruction at offset {from:#06X} passes control to {site}, complexity of which can't be
+    /// analyzed without the called library.
+    ExternalCall { from: u16, site: Site<LibId> },
+
+    /// code cycle at offset {0:#06X} has no exits, such that the program never terminates.
+    NonTerminating(u16),
+
+    /// code cycle at offset {0:#06X} may be repeated unbounded number of times, thus the code
+    /// complexity can't be limited statically.
+    Unbounded(u16),
+
+    /// worst-case complexity of the code exceeds 2^64.
+    Overflow,
+}
+
+/// Node of the control flow graph, representing a single instruction.
+struct Node {
+    pos: u16,
+    complexity: u64,
+    /// Indexes of instructions which may be executed right after this instruction.
+    next: Vec<usize>,
+    /// Indexes of subroutines called by this instruction, which return the control to `next`.
+    calls: Vec<usize>,
+    /// Whether the execution may stop or leave the code segment at this instruction.
+    exit: bool,
+}
+
+impl Node {
+    fn edges(&self) -> impl Iterator<Item = usize> + '_ {
+        self.next.iter().chain(&self.calls).copied()
+    }
+}
+
+impl From<IncompleteInstr> for ComplexityAnalysisError {
+    fn from(err: IncompleteInstr) -> Self { ComplexityAnalysisError::Incomplete(err.0) }
+}
+
+impl Lib {
+    /// Computes an upper bound of the total complexity accumulated by the library code, when it
+    /// is executed from offset zero.
+    ///
+    /// The analysis builds the control flow graph of the code and finds its maximum-weight path;
+    /// local subroutines are accounted at each of their call sites. The virtual machine doesn't
+    /// limit the number of passes through a code cycle, thus code containing a cycle reachable
+    /// from offset zero results in [`ComplexityAnalysisError::Unbounded`], and programs which
+    /// pass the control to other libraries can't be analyzed and result in
+    /// [`ComplexityAnalysisError::ExternalCall`].
+    ///
+    /// Use [`crate::CoreConfig::complexity_lim`] to bound the complexity of such programs at
+    /// runtime.
+    pub fn compute_max_complexity<Isa>(&self) -> Result<u64, ComplexityAnalysisError>
+    where Isa: Instruction<LibId> {
+        let nodes = self.control_flow_graph::<Isa>()?;
+        let sccs = strongly_connected(&nodes);
+        let mut comp = vec![0usize; nodes.len()];
+        for (no, scc) in sccs.iter().enumerate() {
+            for idx in scc {
+                comp[*idx] = no;
+            }
+        }
+
+        let add = |a: u64, b: u64| a.checked_add(b).ok_or(ComplexityAnalysisError::Overflow);
+        let mut cost = vec![0u64; nodes.len()];
+        for (no, scc) in sccs.iter().enumerate() {
+            let first = scc[0];
+            let cyclic = scc.len() > 1 || nodes[first].edges().any(|idx| idx == first);
+            let mut body = 0u64;
+            let mut tail = 0u64;
+            let mut exit = false;
+            for idx in scc {
+                let node = &nodes[*idx];
+                body = add(body, node.complexity)?;
+                for call in node.calls.iter().filter(|idx| comp[**idx] != no) {
+                    body = add(body, cost[*call])?;
+                }
+                exit |= node.exit;
+                for next in node.next.iter().filter(|idx| comp[**idx] != no) {
+                    exit = true;
+                    tail = tail.max(cost[*next]);
+                }
+            }
+            let pos = || {
+                scc.iter()
+                    .map(|idx| nodes[*idx].pos)
+                    .min()
+                    .unwrap_or_default()
+            };
+            if !exit {
+                return Err(ComplexityAnalysisError::NonTerminating(pos()));
+            }
+            if cyclic {
+                return Err(ComplexityAnalysisError::Unbounded(pos()));
+            }
+            let total = add(body, tail)?;
+            for idx in scc {
+                cost[*idx] = total;
+            }
+        }
+
+        Ok(cost.first().copied().unwrap_or_default())
+    }
+
+    fn control_flow_graph<Isa>(&self) -> Result<Vec<Node>, ComplexityAnalysisError>
+    where Isa: Instruction<LibId> {
+        let code = self.instr_offsets::<Isa>()?;
+        let index = code
+            .iter()
+            .enumerate()
+            .map(|(idx, (pos, _, _))| (*pos, idx))
+            .collect::<BTreeMap<_, _>>();
+        let lib_id = self.lib_id();
+        let len = self.code.len();
+
+        let mut nodes = Vec::with_capacity(code.len());
+        for (pos, next, instr) in &code {
+            let from = *pos;
+            // Jumps outside the code segment halt the execution and are not graph edges.
+            let resolve = |target: u16| {
+                if target as usize >= len {
+                    return Ok(None);
+                }
+                index
+                    .get(&target)
+                    .copied()
+                    .map(Some)
+                    .ok_or(ComplexityAnalysisError::InvalidTarget { from, target })
+            };
+
+            let mut targets = instr.successors(from, *next).collect::<Vec<_>>();
+            let mut calls = Vec::new();
+            if let Some(call) = instr.local_call_pos() {
+                if let Some(no) = targets.iter().position(|target| *target == call) {
+                    targets.remove(no);
+                }
+                calls.push(call);
+            }
+            let returns = !targets.is_empty();
+            for site in instr.call_sites() {
+                if site.prog_id != lib_id {
+                    return Err(ComplexityAnalysisError::ExternalCall { from, site });
+                }
+                if returns {
+                    calls.push(site.offset);
+                } else {
+                    targets.push(site.offset);
+                }
+            }
+
+            let mut node = Node {
+                pos: from,
+                complexity: instr.complexity(),
+                next: Vec::with_capacity(targets.len()),
+                calls: Vec::with_capacity(calls.len()),
+                exit: targets.is_empty(),
+            };
+            for target in targets {
+                match resolve(target)? {
+                    Some(idx) => node.next.push(idx),
+                    None => node.exit = true,
+                }
+            }
+            for call in calls {
+                node.calls.extend(resolve(call)?);
+            }
+            nodes.push(node);
+        }
+        Ok(nodes)
+    }
+}
+
+/// Finds strongly connected components of the control flow graph reachable from the first node
+/// using Tarjan's algorithm.
+///
+/// The components are returned in reverse topological order, i.e. each component goes after all
+/// the components reachable from it.
+fn strongly_connected(nodes: &[Node]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let mut index = vec![UNVISITED; nodes.len()];
+    let mut low = vec![0usize; nodes.len()];
+    let mut on_stack = vec![false; nodes.len()];
+    let mut stack = Vec::new();
+    let mut sccs = Vec::new();
+    let mut counter = 0usize;
+
+    if !nodes.is_empty() {
+        let root = 0;
+        let mut work = vec![(root, 0usize)];
+        index[root] = counter;
+        low[root] = counter;
+        counter += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some(&(v, edge)) = work.last() {
+            if let Some(w) = nodes[v].edges().nth(edge) {
+                if let Some(last) = work.last_mut() {
+                    last.1 += 1;
+                }
+                if index[w] == UNVISITED {
+                    index[w] = counter;
+                    low[w] = counter;
+                    counter += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    work.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            work.pop();
+            if let Some(&(u, _)) = work.last() {
+                low[u] = low[u].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut scc = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    scc.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                sccs.push(scc);
+            }
+        }
+    }
+    sccs
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::SmallBlob;
+
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+
+    fn c(instr: CtrlInstr<LibId>) -> u64 { Instr::Ctrl(instr).complexity() }
+
+    fn max_complexity(code: &[CtrlInstr<LibId>]) -> Result<u64, ComplexityAnalysisError> {
+        let code = code.iter().copied().map(Instr::Ctrl).collect::<Vec<_>>();
+        Lib::assemble(&code)
+            .unwrap()
+            .compute_max_complexity::<Instr<LibId>>()
+    }
+
+    #[test]
+    fn straight_line() {
+        let code = [CtrlInstr::Nop, CtrlInstr::Chk, CtrlInstr::Stop];
+        assert_eq!(max_complexity(&code), Ok(code.into_iter().map(c).sum()));
+        assert_eq!(max_complexity(&[]), Ok(0));
+    }
+
+    #[test]
+    fn branches() {
+        let code = [
+            CtrlInstr::JiNe { pos: 5 },
+            CtrlInstr::Nop,
+            CtrlInstr::Stop,
+            CtrlInstr::Chk,
+            CtrlInstr::Stop,
+        ];
+        let left = c(CtrlInstr::Nop) + c(CtrlInstr::Stop);
+        let right = c(CtrlInstr::Chk) + c(CtrlInstr::Stop);
+        assert_eq!(max_complexity(&code), Ok(c(CtrlInstr::JiNe { pos: 5 }) + left.max(right)));
+    }
+
+    #[test]
+    fn unbounded_loop() {
+        let code = [CtrlInstr::Nop, CtrlInstr::JiNe { pos: 0 }, CtrlInstr::Stop];
+        assert_eq!(max_complexity(&code), Err(ComplexityAnalysisError::Unbounded(0)));
+
+        // `NotCo` makes `CO` set, such that `JiNe` loops forever
+        let code = [CtrlInstr::NotCo, CtrlInstr::Nop, CtrlInstr::JiNe { pos: 1 }, CtrlInstr::Stop];
+        assert_eq!(max_complexity(&code), Err(ComplexityAnalysisError::Unbounded(1)));
+    }
+
+    #[test]
+    fn unreachable_loop() {
+        let code = [CtrlInstr::Nop, CtrlInstr::Stop, CtrlInstr::Nop, CtrlInstr::Jmp { pos: 2 }];
+        assert_eq!(max_complexity(&code), Ok(c(CtrlInstr::Nop) + c(CtrlInstr::Stop)));
+    }
+
+    #[test]
+    fn local_call() {
+        let code = [CtrlInstr::Fn { pos: 4 }, CtrlInstr::Stop, CtrlInstr::Nop, CtrlInstr::Ret];
+        assert_eq!(max_complexity(&code), Ok(code.into_iter().map(c).sum()));
+
+        let code =
+            [CtrlInstr::Fn { pos: 7 }, CtrlInstr::Fn { pos: 7 }, CtrlInstr::Stop, CtrlInstr::Ret];
+        let callee = c(CtrlInstr::Ret);
+        let total = 2 * (c(CtrlInstr::Fn { pos: 7 }) + callee) + c(CtrlInstr::Stop);
+        assert_eq!(max_complexity(&code), Ok(total));
+    }
+
+    #[test]
+    fn non_terminating() {
+        let code = [CtrlInstr::Nop, CtrlInstr::Nop, CtrlInstr::Jmp { pos: 1 }];
+        assert_eq!(max_complexity(&code), Err(ComplexityAnalysisError::NonTerminating(1)));
+    }
+
+    #[test]
+    fn unsupported_code() {
+        let site = Site::new(LibId::from([0xA5u8; 32]), 0);
+        assert_eq!(
+            max_complexity(&[CtrlInstr::Nop, CtrlInstr::Call { site }]),
+            Err(ComplexityAnalysisError::ExternalCall { from: 1, site })
+        );
+        assert_eq!(
+            max_complexity(&[CtrlInstr::Nop, CtrlInstr::Jmp { pos: 2 }]),
+            Err(ComplexityAnalysisError::InvalidTarget { from: 1, target: 2 })
+        );
+
+        let mut lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Nop)]).unwrap();
+        lib.code = SmallBlob::from_checked(vec![0x00, 0x05]);
+        assert_eq!(
+            lib.compute_max_complexity::<Instr<LibId>>(),
+            Err(ComplexityAnalysisError::Incomplete(1))
+        );
+    }
+}
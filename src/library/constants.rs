@@ -0,0 +1,171 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeSet;
+
+use amplify::confinement::SmallBlob;
+use amplify::num::{u1, u2, u3, u4, u5, u6, u7};
+
+use super::{Lib, LibId, Marshaller};
+use crate::isa::{BytecodeRead, CodeEofError, Instruction};
+
+/// Constant value stored in the library data segment and referenced from its code segment.
+#[derive(Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub struct DataEntry {
+    /// Offset of the value in the data segment.
+    pub offset: u16,
+    /// Bytes of the value.
+    pub value: SmallBlob,
+}
+
+/// Bytecode reader recording all data segment references made by the instructions.
+struct DataTracker<'a> {
+    reader: Marshaller<'a, &'a [u8], &'a [u8]>,
+    data: &'a [u8],
+    entries: BTreeSet<DataEntry>,
+}
+
+impl<'a> DataTracker<'a> {
+    fn record(&mut self, offset: u16, len: usize) -> Option<&[u8]> {
+        let start = offset as usize;
+        let end = start + len;
+        if end > self.data.len() {
+            return None;
+        }
+        let value = SmallBlob::from_checked(self.data[start..end].to_vec());
+        self.entries.insert(DataEntry { offset, value });
+        Some(&self.data[start..end])
+    }
+}
+
+impl<'a> BytecodeRead<LibId> for DataTracker<'a> {
+    fn pos(&self) -> u16 { self.reader.pos() }
+    fn seek(&mut self, byte_pos: u16) -> Result<u16, CodeEofError> { self.reader.seek(byte_pos) }
+    fn is_eof(&self) -> bool { self.reader.is_eof() }
+    fn remaining_bytes(&self) -> u16 { self.reader.remaining_bytes() }
+    fn peek_byte(&self) -> Result<u8, CodeEofError> { self.reader.peek_byte() }
+
+    fn read_bool(&mut self) -> Result<bool, CodeEofError> { self.reader.read_bool() }
+    fn read_1bit(&mut self) -> Result<u1, CodeEofError> { self.reader.read_1bit() }
+    fn read_2bits(&mut self) -> Result<u2, CodeEofError> { self.reader.read_2bits() }
+    fn read_3bits(&mut self) -> Result<u3, CodeEofError> { self.reader.read_3bits() }
+    fn read_4bits(&mut self) -> Result<u4, CodeEofError> { self.reader.read_4bits() }
+    fn read_5bits(&mut self) -> Result<u5, CodeEofError> { self.reader.read_5bits() }
+    fn read_6bits(&mut self) -> Result<u6, CodeEofError> { self.reader.read_6bits() }
+    fn read_7bits(&mut self) -> Result<u7, CodeEofError> { self.reader.read_7bits() }
+
+    fn read_byte(&mut self) -> Result<u8, CodeEofError> { self.reader.read_byte() }
+    fn read_word(&mut self) -> Result<u16, CodeEofError> { self.reader.read_word() }
+
+    fn read_fixed<N, const LEN: usize>(
+        &mut self,
+        f: impl FnOnce([u8; LEN]) -> N,
+    ) -> Result<N, CodeEofError> {
+        let offset = self.reader.read_word()?;
+        let slice = self.record(offset, LEN).ok_or(CodeEofError)?;
+        let mut buf = [0u8; LEN];
+        buf.copy_from_slice(slice);
+        Ok(f(buf))
+    }
+
+    fn read_bytes(&mut self) -> Result<(SmallBlob, bool), CodeEofError> {
+        let offset = self.reader.read_word()?;
+        let len = self.reader.read_word()? as usize;
+        let available = self.data.len().saturating_sub(offset as usize);
+        let ck = len <= available;
+        let slice = self.record(offset, len.min(available)).unwrap_or_default();
+        Ok((SmallBlob::from_checked(slice.to_vec()), ck))
+    }
+
+    fn read_ref(&mut self) -> Result<LibId, CodeEofError>
+    where LibId: Sized {
+        self.reader.read_ref()
+    }
+
+    fn check_aligned(&self) { self.reader.check_aligned() }
+}
+
+impl Lib {
+    /// Enumerates all constant values from the data segment which are referenced by the
+    /// instructions of the code segment.
+    ///
+    /// # Returns
+    ///
+    /// List of unique constants, ordered by their offset in the data segment.
+    pub fn extract_constants<Isa>(&self) -> Result<Vec<DataEntry>, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let mut tracker = DataTracker {
+            reader: Marshaller::with(self.code.as_slice(), self.data.as_slice(), &self.libs),
+            data: self.data.as_slice(),
+            entries: none!(),
+        };
+        while !tracker.is_eof() {
+            Isa::decode_instr(&mut tracker)?;
+        }
+        Ok(tracker.entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::test_utils::TestInstr;
+    use crate::isa::CtrlInstr;
+
+    #[test]
+    fn extract_constants() {
+        let code = [
+            TestInstr::Const { reg: 0, val: 0xDEADBEEF },
+            TestInstr::Ctrl(CtrlInstr::Nop),
+            TestInstr::Const { reg: 1, val: 0x01020304 },
+            TestInstr::Const { reg: 2, val: 0xDEADBEEF },
+            TestInstr::Ctrl(CtrlInstr::Stop),
+        ];
+        let mut lib = Lib::assemble(&code).unwrap();
+        assert_eq!(lib.disassemble::<TestInstr>().unwrap(), code);
+        let constants = lib.extract_constants::<TestInstr>().unwrap();
+        assert_eq!(constants, vec![
+            DataEntry {
+                offset: 0,
+                value: SmallBlob::from_checked(vec![0xEF, 0xBE, 0xAD, 0xDE])
+            },
+            DataEntry {
+                offset: 4,
+                value: SmallBlob::from_checked(vec![0x04, 0x03, 0x02, 0x01])
+            },
+        ]);
+
+        lib.data.push(0xFF).unwrap();
+        assert_eq!(lib.extract_constants::<TestInstr>().unwrap(), constants);
+    }
+
+    #[test]
+    fn extract_no_constants() {
+        let lib = Lib::assemble(&[CtrlInstr::<LibId>::Nop, CtrlInstr::Stop]).unwrap();
+        assert!(lib
+            .extract_constants::<CtrlInstr<LibId>>()
+            .unwrap()
+            .is_empty());
+    }
+}
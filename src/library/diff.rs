@@ -0,0 +1,449 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeSet;
+use core::fmt::{self, Display, Formatter};
+
+use super::{DataEntry, Lib, LibId};
+use crate::isa::{CodeEofError, Instruction};
+use crate::IsaId;
+
+/// Change of a single instruction between two versions of a library.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum InstrChange<Isa> {
+    /// Instruction present only in the new library version.
+    Added {
+        /// Offset of the instruction in the new library version.
+        offset: u16,
+        /// Added instruction.
+        instr: Isa,
+    },
+
+    /// Instruction present only in the old library version.
+    Removed {
+        /// Offset of the instruction in the old library version.
+        offset: u16,
+        /// Removed instruction.
+        instr: Isa,
+    },
+
+    /// Instruction replaced with another instruction.
+    Modified {
+        /// Offset of the instruction in the old library version.
+        old_offset: u16,
+        /// Offset of the instruction in the new library version.
+        new_offset: u16,
+        /// Instruction from the old library version.
+        old: Isa,
+        /// Instruction from the new library version.
+        new: Isa,
+    },
+}
+
+/// Difference between two versions of a library, produced by [`Lib::diff`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct LibDiff<Isa> {
+    /// Id of the old library version.
+    pub old_id: LibId,
+    /// Id of the new library version.
+    pub new_id: LibId,
+    /// ISA extensions used only by the old library version.
+    pub isae_removed: BTreeSet<IsaId>,
+    /// ISA extensions used only by the new library version.
+    pub isae_added: BTreeSet<IsaId>,
+    /// Dependencies of the old library version absent in the new version.
+    pub libs_removed: BTreeSet<LibId>,
+    /// Dependencies of the new library version absent in the old version.
+    pub libs_added: BTreeSet<LibId>,
+    /// Changed instructions, ordered by their position in the code.
+    pub code: Vec<InstrChange<Isa>>,
+    /// Constants referenced only by the old library version.
+    pub data_removed: Vec<DataEntry>,
+    /// Constants referenced only by the new library version.
+    pub data_added: Vec<DataEntry>,
+}
+
+impl<Isa> LibDiff<Isa> {
+    /// Detects whether both library versions have identical content.
+    pub fn is_empty(&self) -> bool {
+        self.isae_removed.is_empty()
+            && self.isae_added.is_empty()
+            && self.libs_removed.is_empty()
+            && self.libs_added.is_empty()
+            && self.code.is_empty()
+            && self.data_removed.is_empty()
+            && self.data_added.is_empty()
+    }
+}
+
+impl<Isa: Display> Display for LibDiff<Isa> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "--- {}", self.old_id)?;
+        writeln!(f, "+++ {}", self.new_id)?;
+
+        if !self.isae_removed.is_empty() || !self.isae_added.is_empty() {
+            writeln!(f, "@@ isae @@")?;
+            for isa in &self.isae_removed {
+                writeln!(f, "-{isa}")?;
+            }
+            for isa in &self.isae_added {
+                writeln!(f, "+{isa}")?;
+            }
+        }
+
+        if !self.libs_removed.is_empty() || !self.libs_added.is_empty() {
+            writeln!(f, "@@ libs @@")?;
+            for lib in &self.libs_removed {
+                writeln!(f, "-{lib}")?;
+            }
+            for lib in &self.libs_added {
+                writeln!(f, "+{lib}")?;
+            }
+        }
+
+        if !self.code.is_empty() {
+            writeln!(f, "@@ code @@")?;
+            for change in &self.code {
+                match change {
+                    InstrChange::Added { offset, instr } => {
+                        writeln!(f, "+@x{offset:06X}: {instr}")?
+                    }
+                    InstrChange::Removed { offset, instr } => {
+                        writeln!(f, "-@x{offset:06X}: {instr}")?
+                    }
+                    InstrChange::Modified { old_offset, new_offset, old, new } => {
+                        writeln!(f, "-@x{old_offset:06X}: {old}")?;
+                        writeln!(f, "+@x{new_offset:06X}: {new}")?;
+                    }
+                }
+            }
+        }
+
+        if !self.data_removed.is_empty() || !self.data_added.is_empty() {
+            writeln!(f, "@@ data @@")?;
+            for entry in &self.data_removed {
+                writeln!(f, "-@x{:04X}: {:X}", entry.offset, entry.value)?;
+            }
+            for entry in &self.data_added {
+                writeln!(f, "+@x{:04X}: {:X}", entry.offset, entry.value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Lib {
+    /// Computes difference between two versions of a library.
+    ///
+    /// Instructions are compared after decoding, such that changes in the data segment layout
+    /// or the library segment ordering don't produce spurious differences in the code.
+    pub fn diff<Isa>(old: &Lib, new: &Lib) -> Result<LibDiff<Isa>, CodeEofError>
+    where Isa: Instruction<LibId> + PartialEq + Clone {
+        let old_code = old
+            .iter_instructions::<Isa>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let new_code = new
+            .iter_instructions::<Isa>()
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let old_data = old.extract_constants::<Isa>()?;
+        let new_data = new.extract_constants::<Isa>()?;
+        let data_value = |entry: &DataEntry| entry.value.clone();
+        let old_values = old_data.iter().map(data_value).collect::<BTreeSet<_>>();
+        let new_values = new_data.iter().map(data_value).collect::<BTreeSet<_>>();
+
+        Ok(LibDiff {
+            old_id: old.lib_id(),
+            new_id: new.lib_id(),
+            isae_removed: old
+                .isae
+                .iter()
+                .filter(|isa| !new.isae.contains(*isa))
+                .cloned()
+                .collect(),
+            isae_added: new
+                .isae
+                .iter()
+                .filter(|isa| !old.isae.contains(*isa))
+                .cloned()
+                .collect(),
+            libs_removed: old
+                .libs
+                .iter()
+                .filter(|lib| !new.libs.contains(*lib))
+                .copied()
+                .collect(),
+            libs_added: new
+                .libs
+                .iter()
+                .filter(|lib| !old.libs.contains(*lib))
+                .copied()
+                .collect(),
+            code: diff_code(old_code, new_code),
+            data_removed: old_data
+                .into_iter()
+                .filter(|entry| !new_values.contains(&entry.value))
+                .collect(),
+            data_added: new_data
+                .into_iter()
+                .filter(|entry| !old_values.contains(&entry.value))
+                .collect(),
+        })
+    }
+}
+
+/// Computes instruction changes using the longest common subsequence of both code versions.
+///
+/// Sequences of removed instructions immediately followed by added instructions are reported as
+/// modifications.
+fn diff_code<Isa: PartialEq + Clone>(
+    old: Vec<(u16, Isa)>,
+    new: Vec<(u16, Isa)>,
+) -> Vec<InstrChange<Isa>> {
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|((_, a), (_, b))| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|((_, a), (_, b))| a == b)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+
+    let mut script = Vec::with_capacity(old.len() + new.len());
+    edit_script(old, new, &mut script);
+
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let flush = |changes: &mut Vec<InstrChange<Isa>>,
+                 removed: &mut Vec<&(u16, Isa)>,
+                 added: &mut Vec<&(u16, Isa)>| {
+        let modified = removed.len().min(added.len());
+        for ((old_offset, old), (new_offset, new)) in
+            removed.drain(..modified).zip(added.drain(..modified))
+        {
+            changes.push(InstrChange::Modified {
+                old_offset: *old_offset,
+                new_offset: *new_offset,
+                old: old.clone(),
+                new: new.clone(),
+            });
+        }
+        for (offset, instr) in removed.drain(..) {
+            changes.push(InstrChange::Removed { offset: *offset, instr: instr.clone() });
+        }
+        for (offset, instr) in added.drain(..) {
+            changes.push(InstrChange::Added { offset: *offset, instr: instr.clone() });
+        }
+    };
+
+    for edit in script {
+        match edit {
+            Edit::Keep => flush(&mut changes, &mut removed, &mut added),
+            Edit::Remove(instr) => removed.push(instr),
+            Edit::Add(instr) => added.push(instr),
+        }
+    }
+    flush(&mut changes, &mut removed, &mut added);
+    changes
+}
+
+/// Step of the script editing old code version into the new one.
+enum Edit<'a, Isa> {
+    Keep,
+    Remove(&'a (u16, Isa)),
+    Add(&'a (u16, Isa)),
+}
+
+/// Constructs the shortest edit script between two code versions using Hirschberg algorithm,
+/// which requires memory linear in the code size.
+fn edit_script<'a, Isa: PartialEq>(
+    old: &'a [(u16, Isa)],
+    new: &'a [(u16, Isa)],
+    script: &mut Vec<Edit<'a, Isa>>,
+) {
+    if old.is_empty() {
+        script.extend(new.iter().map(Edit::Add));
+        return;
+    }
+    if new.is_empty() {
+        script.extend(old.iter().map(Edit::Remove));
+        return;
+    }
+    if old.len() == 1 {
+        match new.iter().position(|(_, instr)| *instr == old[0].1) {
+            Some(pos) => {
+                script.extend(new[..pos].iter().map(Edit::Add));
+                script.push(Edit::Keep);
+                script.extend(new[pos + 1..].iter().map(Edit::Add));
+            }
+            None => {
+                script.push(Edit::Remove(&old[0]));
+                script.extend(new.iter().map(Edit::Add));
+            }
+        }
+        return;
+    }
+
+    let mid = old.len() / 2;
+    let head = lcs_lengths(&old[..mid], new, false);
+    let tail = lcs_lengths(&old[mid..], new, true);
+    let split = (0..=new.len())
+        .max_by_key(|&k| head[k] + tail[new.len() - k])
+        .unwrap_or_default();
+    edit_script(&old[..mid], &new[..split], script);
+    edit_script(&old[mid..], &new[split..], script);
+}
+
+/// Computes lengths of the longest common subsequences of `old` and all prefixes of `new` (or of
+/// all suffixes, if `rev` is set, in which case both sequences are processed backwards).
+fn lcs_lengths<Isa: PartialEq>(old: &[(u16, Isa)], new: &[(u16, Isa)], rev: bool) -> Vec<u32> {
+    fn item<Isa>(seq: &[(u16, Isa)], i: usize, rev: bool) -> &Isa {
+        if rev {
+            &seq[seq.len() - 1 - i].1
+        } else {
+            &seq[i].1
+        }
+    }
+
+    let mut row = vec![0u32; new.len() + 1];
+    for i in 0..old.len() {
+        let mut diag = 0;
+        for j in 0..new.len() {
+            let up = row[j + 1];
+            row[j + 1] =
+                if item(old, i, rev) == item(new, j, rev) { diag + 1 } else { up.max(row[j]) };
+            diag = up;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+    use crate::Site;
+
+    #[test]
+    fn same_code() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(LibId::from([0xA5u8; 32]), 0x69AB) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ];
+        let old = Lib::assemble(&code).unwrap();
+        let new = Lib::assemble(&code).unwrap();
+        let diff = Lib::diff::<Instr<LibId>>(&old, &new).unwrap();
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), format!("--- {0}\n+++ {0}\n", old.lib_id()));
+    }
+
+    #[test]
+    fn code_changes() {
+        let old = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let new = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 1 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let diff = Lib::diff::<Instr<LibId>>(&old, &new).unwrap();
+        assert!(!diff.is_empty());
+        assert_eq!(diff.code, vec![
+            InstrChange::Modified {
+                old_offset: 1,
+                new_offset: 1,
+                old: Instr::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+                new: Instr::Ctrl(CtrlInstr::Nop),
+            },
+            InstrChange::Added { offset: 2, instr: Instr::Ctrl(CtrlInstr::Jmp { pos: 1 }) },
+        ]);
+        let text = diff.to_string();
+        assert!(text.contains("@@ code @@\n"));
+        assert!(text.contains("+@x000002: "));
+        assert!(!text.contains("@@ libs @@"));
+    }
+
+    #[test]
+    fn libs_changes() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let old = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Stop)]).unwrap();
+        let new = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(lib_id, 0) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let diff = Lib::diff::<Instr<LibId>>(&old, &new).unwrap();
+        assert_eq!(diff.libs_added, bset![lib_id]);
+        assert!(diff.libs_removed.is_empty());
+        assert_eq!(diff.code.len(), 1);
+        assert!(diff
+            .to_string()
+            .contains(&format!("@@ libs @@\n+{lib_id}\n")));
+
+        let diff = Lib::diff::<Instr<LibId>>(&new, &old).unwrap();
+        assert_eq!(diff.libs_removed, bset![lib_id]);
+        assert!(matches!(diff.code[0], InstrChange::Removed { offset: 0, .. }));
+    }
+
+    #[test]
+    fn large_code() {
+        let old = (0..0x1000)
+            .map(|pos| Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos }))
+            .collect::<Vec<_>>();
+        let mut new = old.clone();
+        new[0x0A00] = Instr::Ctrl(CtrlInstr::Stop);
+        new.remove(0x0C00);
+        new.insert(0x0100, Instr::Ctrl(CtrlInstr::NotCo));
+        let old = Lib::assemble(&old).unwrap();
+        let new = Lib::assemble(&new).unwrap();
+        let diff = Lib::diff::<Instr<LibId>>(&old, &new).unwrap();
+        assert_eq!(diff.code, vec![
+            InstrChange::Added { offset: 0x0300, instr: Instr::Ctrl(CtrlInstr::NotCo) },
+            InstrChange::Modified {
+                old_offset: 0x1E00,
+                new_offset: 0x1E01,
+                old: Instr::Ctrl(CtrlInstr::Jmp { pos: 0x0A00 }),
+                new: Instr::Ctrl(CtrlInstr::Stop),
+            },
+            InstrChange::Removed {
+                offset: 0x2400,
+                instr: Instr::Ctrl(CtrlInstr::Jmp { pos: 0x0C00 })
+            },
+        ]);
+    }
+}
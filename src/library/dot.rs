@@ -0,0 +1,178 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::BTreeSet;
+use core::fmt::Write;
+
+use super::{Lib, LibId};
+use crate::isa::{CodeEofError, Instruction};
+
+/// Instruction decoded for the purposes of the control-flow analysis.
+struct Node<Isa> {
+    pos: u16,
+    next: u16,
+    instr: Isa,
+}
+
+impl Lib {
+    /// Generates control-flow graph of the library code in Graphviz DOT format.
+    ///
+    /// The graph contains a node per each basic block (straight-line code between branches and
+    /// branch targets), labeled with its byte offset range and instructions, and edges for each
+    /// possible successor of the block, including fall-through. Calls into other programs are
+    /// shown as dashed edges to nodes labeled with the called site.
+    pub fn to_dot<Isa>(&self) -> Result<String, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let nodes = self
+            .instr_offsets::<Isa>()?
+            .into_iter()
+            .map(|(pos, next, instr)| Node { pos, next, instr })
+            .collect::<Vec<_>>();
+
+        let offsets = nodes.iter().map(|node| node.pos).collect::<BTreeSet<_>>();
+        let mut leaders = BTreeSet::from([0u16]);
+        for node in &nodes {
+            let mut successors = node.instr.successors(node.pos, node.next);
+            if successors.next() != Some(node.next) || successors.next().is_some() {
+                leaders.insert(node.next);
+                leaders.extend(node.instr.successors(node.pos, node.next));
+            }
+        }
+        leaders.retain(|pos| offsets.contains(pos));
+
+        let mut dot = String::new();
+        let mut external = BTreeSet::new();
+        let mut dangling = BTreeSet::new();
+        writeln!(dot, "digraph \"{}\" {{", self.lib_id()).expect("strings are infallible");
+        writeln!(dot, "    node [shape=box, fontname=monospace];").expect("strings are infallible");
+        let mut blocks = nodes.iter().peekable();
+        while let Some(first) = blocks.next() {
+            let mut block = vec![first];
+            while let Some(node) = blocks.next_if(|node| !leaders.contains(&node.pos)) {
+                block.push(node);
+            }
+            let last = block[block.len() - 1];
+
+            let mut label = format!("@x{:04X}..@x{:04X}\\l", first.pos, last.next);
+            for node in &block {
+                let instr = node
+                    .instr
+                    .to_string()
+                    .replace('\\', "\\\\")
+                    .replace('"', "\\\"");
+                write!(label, "@x{:04X}: {instr}\\l", node.pos).expect("strings are infallible");
+            }
+            writeln!(dot, "    b{:04X} [label=\"{label}\"];", first.pos)
+                .expect("strings are infallible");
+
+            for succ in last.instr.successors(last.pos, last.next) {
+                if !leaders.contains(&succ) {
+                    dangling.insert(succ);
+                }
+                writeln!(dot, "    b{:04X} -> b{succ:04X};", first.pos)
+                    .expect("strings are infallible");
+            }
+            for site in block.iter().flat_map(|node| node.instr.call_sites()) {
+                external.insert(site);
+                writeln!(
+                    dot,
+                    "    b{:04X} -> \"{site}\" [style=dashed, label=\"{}\"];",
+                    first.pos, site.prog_id
+                )
+                .expect("strings are infallible");
+            }
+        }
+        for pos in dangling {
+            writeln!(dot, "    b{pos:04X} [label=\"@x{pos:04X}: <invalid target>\", shape=plain];")
+                .expect("strings are infallible");
+        }
+        for site in external {
+            writeln!(dot, "    \"{site}\" [label=\"@x{:04X}\", shape=ellipse];", site.offset)
+                .expect("strings are infallible");
+        }
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+    use crate::Site;
+
+    fn edges(dot: &str) -> Vec<(u16, u16)> {
+        dot.lines()
+            .filter_map(|line| {
+                let (from, to) = line.trim().strip_suffix(';')?.split_once(" -> ")?;
+                let from = u16::from_str_radix(from.strip_prefix('b')?, 16).ok()?;
+                let to = u16::from_str_radix(to.strip_prefix('b')?, 16).ok()?;
+                Some((from, to))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn simple_loop() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 1 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let dot = lib.to_dot::<Instr<LibId>>().unwrap();
+        assert!(dot.starts_with(&format!("digraph \"{}\" {{\n", lib.lib_id())));
+        assert!(dot.ends_with("}\n"));
+        assert!(dot.contains("    b0000 [label=\"@x0000..@x0001\\l@x0000: nop\\l\"];\n"));
+        assert!(dot.contains("    b0001 [label=\"@x0001..@x0005\\l"));
+        assert!(dot.contains("    b0005 [label=\"@x0005..@x0006\\l@x0005: stop\\l\"];\n"));
+
+        let edges = edges(&dot);
+        assert_eq!(edges, vec![(0, 1), (1, 1), (1, 5)]);
+        assert_eq!(edges.iter().filter(|(from, to)| to <= from).count(), 1);
+    }
+
+    #[test]
+    fn external_calls() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let site = Site::new(lib_id, 0x69AB);
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site }),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 0x1000 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Exec { site }),
+        ])
+        .unwrap();
+        let dot = lib.to_dot::<Instr<LibId>>().unwrap();
+        assert!(
+            dot.contains(&format!("    b0000 -> \"{site}\" [style=dashed, label=\"{lib_id}\"];\n"))
+        );
+        assert!(
+            dot.contains(&format!("    b0007 -> \"{site}\" [style=dashed, label=\"{lib_id}\"];\n"))
+        );
+        assert!(dot.contains(&format!("    \"{site}\" [label=\"@x69AB\", shape=ellipse];\n")));
+        assert!(dot.contains("    b1000 [label=\"@x1000: <invalid target>\", shape=plain];\n"));
+        assert_eq!(edges(&dot), vec![(0, 0x1000)]);
+    }
+}
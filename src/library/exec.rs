@@ -26,7 +26,7 @@
 use baid64::DisplayBaid64;
 
 use super::{Lib, Marshaller};
-use crate::isa::{Bytecode, BytecodeRead, ExecStep, Instruction};
+use crate::isa::{Bytecode, BytecodeRead, ExecStep, Instruction, IsaId, ISA_DEBUG};
 use crate::{Core, LibId, LibSite, Site};
 
 impl Lib {
@@ -41,6 +41,27 @@ impl Lib {
         registers: &mut Core<LibId, Instr::Core>,
         context: &Instr::Context<'_>,
     ) -> Option<LibSite>
+    where
+        Instr: Instruction<LibId> + Bytecode<LibId>,
+    {
+        self.exec_with::<Instr>(entrypoint, registers, context, |_, _, _| true)
+    }
+
+    /// Execute library code starting at entrypoint, calling `guard` before the execution of each
+    /// instruction.
+    ///
+    /// If the `guard` returns `false`, the execution is halted and `CK` is set to a failed state.
+    ///
+    /// # Returns
+    ///
+    /// Location for the external code jump, if any.
+    pub(crate) fn exec_with<Instr>(
+        &self,
+        entrypoint: u16,
+        registers: &mut Core<LibId, Instr::Core>,
+        context: &Instr::Context<'_>,
+        mut guard: impl FnMut(Site<LibId>, &Instr, &Core<LibId, Instr::Core>) -> bool,
+    ) -> Option<LibSite>
     where
         Instr: Instruction<LibId> + Bytecode<LibId>,
     {
@@ -73,6 +94,8 @@ impl Lib {
         #[cfg(feature = "log")]
         let mut ck0 = registers.ck();
 
+        let debug = self.isae.contains(&IsaId::from(ISA_DEBUG));
+
         while !marshaller.is_eof() {
             let pos = marshaller.pos();
 
@@ -91,7 +114,26 @@ impl Lib {
                 }
             }
 
-            let next = instr.exec(Site::new(lib_id, pos), registers, context);
+            // Breakpoints in libraries not declaring the DEBUG extension execute as reserved ones
+            if !debug && instr.breakpoint_id().is_some() {
+                let _ = registers.acc_complexity(u64::MAX);
+                let _ = registers.fail_ck();
+                #[cfg(feature = "log")]
+                eprintln!(
+                    "undeclared DEBUG ISA extension; halting, {d}CK{z} is set to {r}false{z}"
+                );
+                return None;
+            }
+
+            let site = Site::new(lib_id, pos);
+            if !guard(site, &instr, registers) {
+                let _ = registers.fail_ck();
+                #[cfg(feature = "log")]
+                eprintln!("execution interrupted; halting, {d}CK{z} is set to {r}fail{z}");
+                return None;
+            }
+
+            let next = instr.exec(site, registers, context);
 
             #[cfg(feature = "log")]
             {
@@ -112,9 +154,10 @@ impl Lib {
                 ck0 = registers.ck();
             }
 
-            if !registers.acc_complexity(instr.complexity()) {
+            if registers.acc_complexity(instr.complexity()) {
+                let _ = registers.fail_ck();
                 #[cfg(feature = "log")]
-                eprintln!("complexity overflow");
+                eprintln!("complexity overflow; halting, {d}CK{z} is set to {r}fail{z}");
                 return None;
             }
             match next {
@@ -0,0 +1,118 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use super::{Lib, LibId};
+use crate::isa::{CodeEofError, Instruction};
+
+/// Model mapping AluVM instruction complexity to a gas-like resource unit of a host environment.
+///
+/// The trait is generic over the instruction set, such that the models can be used as trait
+/// objects.
+pub trait GasModel<Isa>
+where Isa: Instruction<LibId>
+{
+    /// Returns amount of gas charged for a single execution of the instruction.
+    fn gas_per_complexity_unit(&self, instr: &Isa) -> u64;
+}
+
+/// Gas model charging the same amount of gas for each instruction.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ConstantGasModel(pub u64);
+
+impl<Isa> GasModel<Isa> for ConstantGasModel
+where Isa: Instruction<LibId>
+{
+    fn gas_per_complexity_unit(&self, _: &Isa) -> u64 { self.0 }
+}
+
+/// Gas model charging the given amount of gas per each unit of instruction complexity.
+///
+/// The default model charges a single gas unit per complexity unit.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ComplexityProportionalGasModel(pub u64);
+
+impl Default for ComplexityProportionalGasModel {
+    fn default() -> Self { Self(1) }
+}
+
+impl<Isa> GasModel<Isa> for ComplexityProportionalGasModel
+where Isa: Instruction<LibId>
+{
+    fn gas_per_complexity_unit(&self, instr: &Isa) -> u64 {
+        instr.complexity().saturating_mul(self.0)
+    }
+}
+
+impl Lib {
+    /// Estimates gas required to execute each instruction of the library code once, using the
+    /// provided gas model.
+    ///
+    /// The estimation doesn't account for jumps and cycles; the result saturates at `u64::MAX`.
+    pub fn estimate_gas<Isa, G>(&self, model: &G) -> Result<u64, CodeEofError>
+    where
+        Isa: Instruction<LibId>,
+        G: GasModel<Isa> + ?Sized,
+    {
+        let mut gas = 0u64;
+        for res in self.iter_instructions::<Isa>() {
+            let (_, instr) = res?;
+            gas = gas.saturating_add(model.gas_per_complexity_unit(&instr));
+        }
+        Ok(gas)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+
+    #[test]
+    fn estimate_gas() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Fn { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::Sh { shift: 2 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Ret),
+        ])
+        .unwrap();
+
+        let default = ComplexityProportionalGasModel::default();
+        let gas = lib.estimate_gas::<Instr<LibId>, _>(&default).unwrap();
+        assert!(gas > 0);
+        assert_eq!(Ok(gas), lib.compute_max_complexity::<Instr<LibId>>());
+        assert_eq!(
+            lib.estimate_gas::<Instr<LibId>, _>(&ComplexityProportionalGasModel(3)),
+            Ok(gas * 3)
+        );
+        assert_eq!(lib.estimate_gas::<Instr<LibId>, _>(&ConstantGasModel(10)), Ok(40));
+
+        let models: [&dyn GasModel<Instr<LibId>>; 2] = [&ConstantGasModel(10), &default];
+        let estimates = models
+            .into_iter()
+            .map(|model| lib.estimate_gas(model))
+            .collect::<Result<Vec<_>, _>>();
+        assert_eq!(estimates, Ok(vec![40, gas]));
+    }
+}
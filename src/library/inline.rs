@@ -0,0 +1,234 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use super::{AssemblerError, Lib, LibId, MarshallError};
+use crate::isa::{CtrlInstr, Instruction};
+
+/// Errors inlining called library code.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum InlineError {
+    /// instruction at offset {0:#06X} is not a call or execution of other library code.
+    NotCall(u16),
+
+    /// instruction at offset {0:#06X} calls library {1} instead of the inlined library.
+    CalleeMismatch(u16, LibId),
+
+    /// instruction at offset {0:#06X} enters the inlined library at offset {1:#06X}, which is not
+    /// a start of an instruction in the library code.
+    InvalidEntry(u16, u16),
+
+    /// combined code of both libraries exceeds 65535 bytes.
+    CodeOverflow,
+
+    /// combined ISA extensions of both libraries exceed 255 entries.
+    IsaeOverflow,
+
+    /// {0}
+    #[from]
+    #[from(MarshallError)]
+    Assembler(AssemblerError),
+}
+
+impl Lib {
+    /// Inlines the code of the `callee` library into this library, replacing the call into the
+    /// callee located at the `call_site` offset.
+    ///
+    /// The callee code is appended to the end of the code segment, with all its local jump
+    /// targets adjusted. The `call` instruction at the call site is replaced with a local `fn`
+    /// call (and `exec` with `jmp`) into the appended code, padded with `nop`, such that the
+    /// offsets of the rest of the code are left unchanged and the program execution produces the
+    /// same result.
+    pub fn inline_call<Isa>(&self, callee: &Lib, call_site: u16) -> Result<Lib, InlineError>
+    where Isa: Instruction<LibId> + From<CtrlInstr<LibId>> + PartialEq {
+        let code = self
+            .iter_instructions::<Isa>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(MarshallError::from)?;
+        let Some(pos) = code.iter().position(|(offset, _)| *offset == call_site) else {
+            return Err(InlineError::NotCall(call_site));
+        };
+        let (_, call) = &code[pos];
+        let Some(site) = call.call_sites().next() else {
+            return Err(InlineError::NotCall(call_site));
+        };
+        if site.prog_id != callee.lib_id() {
+            return Err(InlineError::CalleeMismatch(call_site, site.prog_id));
+        }
+        let callee_code = callee
+            .iter_instructions::<Isa>()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(MarshallError::from)?;
+        if !callee_code.iter().any(|(offset, _)| *offset == site.offset) {
+            return Err(InlineError::InvalidEntry(call_site, site.offset));
+        }
+
+        let base = u16::try_from(self.code.len()).map_err(|_| InlineError::CodeOverflow)?;
+        if self.code.len() + callee.code.len() > u16::MAX as usize {
+            return Err(InlineError::CodeOverflow);
+        }
+        let entry = base
+            .checked_add(site.offset)
+            .ok_or(InlineError::CodeOverflow)?;
+        let replacement = if *call == Isa::from(CtrlInstr::Call { site }) {
+            CtrlInstr::Fn { pos: entry }
+        } else if *call == Isa::from(CtrlInstr::Exec { site }) {
+            CtrlInstr::Jmp { pos: entry }
+        } else {
+            return Err(InlineError::NotCall(call_site));
+        };
+
+        let mut inlined = Vec::with_capacity(code.len() + 1);
+        for (index, (_, instr)) in code.into_iter().enumerate() {
+            if index == pos {
+                inlined.push(Isa::from(replacement));
+                inlined.push(Isa::from(CtrlInstr::Nop));
+            } else {
+                inlined.push(instr);
+            }
+        }
+        for (_, mut instr) in callee_code {
+            if let Some(pos) = instr.local_goto_pos() {
+                *pos = pos.checked_add(base).ok_or(InlineError::CodeOverflow)?;
+            }
+            inlined.push(instr);
+        }
+
+        let mut lib = Lib::assemble(&inlined)?;
+        lib.isae = self.isae.clone();
+        lib.isae
+            .extend(callee.isae.iter().cloned())
+            .map_err(|_| InlineError::IsaeOverflow)?;
+        Ok(lib)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::SmallBlob;
+
+    use super::*;
+    use crate::core::Status;
+    use crate::isa::Instr;
+    use crate::{LibSite, Site, Vm};
+
+    fn callee() -> Lib {
+        Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 5 }),
+            Instr::Ctrl(CtrlInstr::FailCk),
+            Instr::<LibId>::Ctrl(CtrlInstr::Ret),
+        ])
+        .unwrap()
+    }
+
+    fn run(entry: &Lib, libs: &[&Lib]) -> (Status, bool, u16) {
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec(LibSite::new(entry.lib_id(), 0), &(), |id| {
+            libs.iter().copied().find(|lib| lib.lib_id() == id)
+        });
+        (status, vm.core.co(), vm.core.cp())
+    }
+
+    #[test]
+    fn inline_call() {
+        let callee = callee();
+        let caller = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(callee.lib_id(), 0) }),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let inlined = caller.inline_call::<Instr<LibId>>(&callee, 1).unwrap();
+        assert!(inlined.libs.is_empty());
+        assert_eq!(inlined.disassemble::<Instr<LibId>>().unwrap(), [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 6 }),
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 11 }),
+            Instr::Ctrl(CtrlInstr::FailCk),
+            Instr::Ctrl(CtrlInstr::Ret),
+        ]);
+
+        let expected = run(&caller, &[&caller, &callee]);
+        assert_eq!(expected, (Status::Ok, true, 0));
+        assert_eq!(run(&inlined, &[&inlined]), expected);
+    }
+
+    #[test]
+    fn inline_exec() {
+        let callee = callee();
+        let caller = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Fn { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(callee.lib_id(), 0) }),
+        ])
+        .unwrap();
+        let inlined = caller.inline_call::<Instr<LibId>>(&callee, 4).unwrap();
+        let expected = run(&caller, &[&caller, &callee]);
+        assert_eq!(expected, (Status::Ok, true, 0));
+        assert_eq!(run(&inlined, &[&inlined]), expected);
+    }
+
+    #[test]
+    fn inline_errors() {
+        let callee = callee();
+        let other = LibId::from([0xA5u8; 32]);
+        let caller = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(other, 0) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        assert_eq!(caller.inline_call::<Instr<LibId>>(&callee, 0), Err(InlineError::NotCall(0)));
+        assert_eq!(caller.inline_call::<Instr<LibId>>(&callee, 2), Err(InlineError::NotCall(2)));
+        assert_eq!(
+            caller.inline_call::<Instr<LibId>>(&callee, 1),
+            Err(InlineError::CalleeMismatch(1, other))
+        );
+
+        for entry in [2, 6, 0x69AB] {
+            let caller = Lib::assemble(&[
+                Instr::Ctrl(CtrlInstr::Call { site: Site::new(callee.lib_id(), entry) }),
+                Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+            ])
+            .unwrap();
+            assert_eq!(
+                caller.inline_call::<Instr<LibId>>(&callee, 0),
+                Err(InlineError::InvalidEntry(0, entry))
+            );
+        }
+
+        let mut large = callee.clone();
+        large.code = SmallBlob::from_checked(vec![0u8; 0xFFFC]);
+        let caller = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(large.lib_id(), 0) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        assert_eq!(caller.inline_call::<Instr<LibId>>(&large, 0), Err(InlineError::CodeOverflow));
+    }
+}
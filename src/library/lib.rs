@@ -32,7 +32,9 @@ use baid64::{Baid64ParseError, DisplayBaid64, FromBaid64Str};
 use commit_verify::{CommitId, CommitmentId, Digest, Sha256};
 use strict_encoding::{StrictDeserialize, StrictSerialize};
 
+use super::Marshaller;
 use crate::core::SiteId;
+use crate::isa::{BytecodeRead, CodeEofError, Instruction};
 use crate::{IsaId, Site, LIB_NAME_ALUVM};
 
 pub const LIB_ID_TAG: &str = "urn:ubideco:aluvm:lib:v01#241020";
@@ -86,6 +88,9 @@ pub struct LibSite {
     pub offset: u16,
 }
 
+impl StrictSerialize for LibSite {}
+impl StrictDeserialize for LibSite {}
+
 impl From<Site<LibId>> for LibSite {
     fn from(site: Site<LibId>) -> Self { Self { lib_id: site.prog_id, offset: site.offset } }
 }
@@ -93,10 +98,80 @@ impl From<Site<LibId>> for LibSite {
 impl LibSite {
     #[inline]
     pub fn new(lib_id: LibId, offset: u16) -> Self { LibSite { lib_id, offset } }
+
+    /// Detects whether the site belongs to the library.
+    pub fn is_local(&self, lib: &Lib) -> bool { self.lib_id == lib.lib_id() }
+
+    /// Detects whether the site belongs to the library and points inside its code segment.
+    pub fn in_range(&self, lib: &Lib) -> bool {
+        self.is_local(lib) && (self.offset as usize) < lib.code.len()
+    }
+
+    /// Decodes a single instruction located at the site.
+    ///
+    /// # Errors
+    ///
+    /// Errors with [`CodeEofError`] if the site doesn't belong to the library, points outside its
+    /// code segment, or the instruction at the site is incomplete.
+    pub fn instruction_at<Isa>(&self, lib: &Lib) -> Result<Isa, CodeEofError>
+    where Isa: Instruction<LibId> {
+        if !self.is_local(lib) {
+            return Err(CodeEofError);
+        }
+        let mut reader = Marshaller::with(&lib.code, &lib.data, &lib.libs);
+        reader.seek(self.offset)?;
+        Isa::decode_instr(&mut reader)
+    }
 }
 
 pub type LibsSeg = TinyOrdSet<LibId>;
 
+/// Lookup table for CRC-32C (Castagnoli) checksum, using reversed polynomial `0x82F63B78`.
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0x82F6_3B78 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc32c<'a>(data: impl IntoIterator<Item = &'a u8>) -> u32 {
+    !data
+        .into_iter()
+        .fold(!0u32, |crc, byte| CRC32C_TABLE[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8))
+}
+
+/// Error indicating that the library segment can't hold more dependencies.
+#[derive(Clone, Copy, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display("library segment can't hold more than 255 dependencies")]
+pub struct LibsSegOverflow;
+
+/// Error indicating that a called library doesn't support some of the ISA extensions used by the
+/// caller.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Error)]
+pub struct IsaeCompatError {
+    /// ISA extensions used by the caller and absent in the called library.
+    pub missing: TinyOrdSet<IsaId>,
+}
+
+impl Display for IsaeCompatError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("called library doesn't support ISA extensions")?;
+        for isa in &self.missing {
+            write!(f, " {isa}")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Ord, PartialOrd, Hash, Debug)]
 #[derive(StrictType, StrictDumb, StrictEncode, StrictDecode)]
 #[strict_type(lib = LIB_NAME_ALUVM)]
@@ -114,6 +189,20 @@ impl StrictSerialize for Lib {}
 impl StrictDeserialize for Lib {}
 
 impl Lib {
+    /// Constructs library from the raw segments without checking that the code segment is a valid
+    /// bytecode for the declared ISA extensions.
+    ///
+    /// Must be used only with the data coming from trusted sources; otherwise
+    /// [`Lib::from_bytecode_validated`] should be used.
+    pub fn from_bytecode_unchecked(
+        isae: TinyOrdSet<IsaId>,
+        code: SmallBlob,
+        data: SmallBlob,
+        libs: LibsSeg,
+    ) -> Lib {
+        Lib { isae, code, data, libs }
+    }
+
     pub fn lib_id(&self) -> LibId { self.commit_id() }
 
     pub fn isae_string(&self) -> String {
@@ -123,6 +212,70 @@ impl Lib {
             .collect::<Vec<_>>()
             .join(" ")
     }
+
+    /// Computes CRC-32C checksum of the code segment.
+    ///
+    /// The checksum is not cryptographically secure and is intended only for fast detection of
+    /// accidental corruption. Use [`Lib::lib_id`] to identify the library.
+    pub fn code_checksum(&self) -> u32 { crc32c(self.code.as_slice()) }
+
+    /// Computes CRC-32C checksum of the data segment.
+    ///
+    /// See [`Lib::code_checksum`] for the details.
+    pub fn data_checksum(&self) -> u32 { crc32c(self.data.as_slice()) }
+
+    /// Computes CRC-32C checksum of the library segment, taken over the concatenated ids of the
+    /// dependencies in the segment order.
+    ///
+    /// See [`Lib::code_checksum`] for the details.
+    pub fn libs_checksum(&self) -> u32 { crc32c(self.libs.iter().flat_map(|id| id.as_slice())) }
+
+    /// Returns iterator over the ids of the libraries this library depends on.
+    pub fn dependency_ids(&self) -> impl Iterator<Item = LibId> + '_ { self.libs.iter().copied() }
+
+    /// Checks whether the library with a given id is a part of the library segment.
+    pub fn has_dependency(&self, id: LibId) -> bool { self.libs.contains(&id) }
+
+    /// Returns number of the libraries this library depends on.
+    pub fn dependency_count(&self) -> usize { self.libs.len() }
+
+    /// Adds a library to the library segment. Does nothing if the library is already present.
+    ///
+    /// Since the library segment is ordered, adding a new dependency may change the position of
+    /// other libraries in the segment, which are referenced from the code segment. Thus, this
+    /// method must be used only for constructing library segment before the code is assembled.
+    ///
+    /// # Errors
+    ///
+    /// If the library segment already contains the maximum number of libraries.
+    pub fn add_dependency(&mut self, id: LibId) -> Result<(), LibsSegOverflow> {
+        if self.has_dependency(id) {
+            return Ok(());
+        }
+        self.libs.push(id).map_err(|_| LibsSegOverflow)
+    }
+
+    /// Checks whether this library can call into the `other` library, i.e. whether the `other`
+    /// library supports all ISA extensions used by this library.
+    ///
+    /// The check is directional: calling into a library with more ISA extensions is allowed.
+    ///
+    /// # Errors
+    ///
+    /// Lists ISA extensions which are present in this library and absent in the `other` one.
+    pub fn validate_isae_compatibility(&self, other: &Lib) -> Result<(), IsaeCompatError> {
+        let missing = self
+            .isae
+            .iter()
+            .filter(|isa| !other.isae.contains(*isa))
+            .cloned();
+        let missing = TinyOrdSet::from_iter_checked(missing);
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(IsaeCompatError { missing })
+        }
+    }
 }
 
 impl Display for Lib {
@@ -130,7 +283,7 @@ impl Display for Lib {
         writeln!(f, "ISAE:  {}", self.isae_string())?;
         writeln!(f, "CODE: {:x}", self.code)?;
         writeln!(f, "DATA: {:x}", self.data)?;
-        if self.libs.len() > 0 {
+        if !self.libs.is_empty() {
             writeln!(
                 f,
                 "LIBS: {:8}",
@@ -148,9 +301,12 @@ impl Display for Lib {
 
 #[cfg(test)]
 mod test {
+    use amplify::confinement::{Confined, U24 as U24MAX};
+    use amplify::hex::{FromHex, ToHex};
     use strict_encoding::StrictDumb;
 
     use super::*;
+    use crate::isa::{CtrlInstr, Instr};
 
     #[test]
     fn lib_id_display() {
@@ -188,4 +344,127 @@ mod test {
 
         assert_eq!(id, LibId::from_str("uZkzX1J9i5EvGTfJ1TB79pOBvKq5x1U2n4qd8Nso3Ag").unwrap());
     }
+
+    #[test]
+    fn dependencies() {
+        let mut lib = Lib::strict_dumb();
+        let id1 = LibId::from([1u8; 32]);
+        let id2 = LibId::from([2u8; 32]);
+        assert_eq!(lib.dependency_count(), 0);
+        assert!(!lib.has_dependency(id1));
+
+        lib.add_dependency(id2).unwrap();
+        lib.add_dependency(id1).unwrap();
+        lib.add_dependency(id2).unwrap();
+        assert_eq!(lib.dependency_count(), 2);
+        assert!(lib.has_dependency(id1));
+        assert!(lib.has_dependency(id2));
+        assert!(!lib.has_dependency(LibId::from([3u8; 32])));
+        assert_eq!(lib.dependency_ids().collect::<Vec<_>>(), vec![id1, id2]);
+    }
+
+    #[test]
+    fn dependencies_overflow() {
+        let mut lib = Lib::strict_dumb();
+        for no in 0..=u8::MAX {
+            let mut id = [0u8; 32];
+            id[0] = no;
+            if no < u8::MAX {
+                lib.add_dependency(LibId::from(id)).unwrap();
+            } else {
+                assert_eq!(lib.add_dependency(LibId::from(id)), Err(LibsSegOverflow));
+            }
+        }
+        assert_eq!(lib.dependency_count(), u8::MAX as usize);
+        // Re-adding an existing dependency into a full segment is not an error
+        lib.add_dependency(LibId::from([0u8; 32])).unwrap();
+        assert_eq!(lib.dependency_count(), u8::MAX as usize);
+    }
+
+    #[test]
+    fn site_in_lib() {
+        let code = [Instr::Ctrl(CtrlInstr::Nop), Instr::Ctrl(CtrlInstr::Jmp { pos: 0 })];
+        let lib = Lib::assemble(&code).unwrap();
+        let other = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Stop)]).unwrap();
+
+        let site = LibSite::new(lib.lib_id(), 1);
+        assert!(site.is_local(&lib));
+        assert!(site.in_range(&lib));
+        assert_eq!(site.instruction_at::<Instr<LibId>>(&lib), Ok(code[1]));
+        assert!(!site.is_local(&other));
+        assert!(!site.in_range(&other));
+        assert_eq!(site.instruction_at::<Instr<LibId>>(&other), Err(CodeEofError));
+
+        let site = LibSite::new(lib.lib_id(), 4);
+        assert!(site.is_local(&lib));
+        assert!(!site.in_range(&lib));
+        assert_eq!(site.instruction_at::<Instr<LibId>>(&lib), Err(CodeEofError));
+    }
+
+    #[test]
+    fn isae_compatibility() {
+        let mut caller = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Stop)]).unwrap();
+        let mut callee = caller.clone();
+        assert_eq!(caller.validate_isae_compatibility(&callee), Ok(()));
+
+        callee.isae.push(IsaId::from("BPDIGEST")).unwrap();
+        assert_eq!(caller.validate_isae_compatibility(&callee), Ok(()));
+        let err = callee.validate_isae_compatibility(&caller).unwrap_err();
+        assert_eq!(err.missing, tiny_bset![IsaId::from("BPDIGEST")]);
+        assert_eq!(err.to_string(), "called library doesn't support ISA extensions BPDIGEST");
+
+        caller.isae.push(IsaId::from("GFA")).unwrap();
+        caller.isae.push(IsaId::from("BPDIGEST")).unwrap();
+        let err = caller.validate_isae_compatibility(&callee).unwrap_err();
+        assert_eq!(err.missing, tiny_bset![IsaId::from("GFA")]);
+    }
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789"), 0xE306_9283);
+        assert_eq!(crc32c(b""), 0);
+    }
+
+    #[test]
+    fn checksums() {
+        let code = [Instr::Ctrl(CtrlInstr::Nop), Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos: 0 })];
+        let lib = Lib::assemble(&code).unwrap();
+        let same = Lib::assemble(&code).unwrap();
+        assert_eq!(lib.code_checksum(), same.code_checksum());
+        assert_eq!(lib.data_checksum(), same.data_checksum());
+        assert_eq!(lib.libs_checksum(), same.libs_checksum());
+
+        let mut changed = lib.clone();
+        changed.code[2] = 0x01;
+        assert_ne!(changed.code_checksum(), lib.code_checksum());
+        assert_eq!(changed.data_checksum(), lib.data_checksum());
+
+        changed.data.push(0x00).unwrap();
+        assert_ne!(changed.data_checksum(), lib.data_checksum());
+
+        changed.add_dependency(LibId::from([0xA5u8; 32])).unwrap();
+        assert_ne!(changed.libs_checksum(), lib.libs_checksum());
+        assert_eq!(lib.libs_checksum(), crc32c(b""));
+    }
+
+    #[test]
+    fn lib_strict_encoding() {
+        const HEX: &str = "0103414c550000000000";
+        let lib =
+            Lib::from_bytecode_unchecked(tiny_bset![IsaId::from("ALU")], none!(), none!(), none!());
+        let data = lib.to_strict_serialized::<U24MAX>().unwrap();
+        assert_eq!(data.to_hex(), HEX);
+        let data = Confined::try_from(Vec::<u8>::from_hex(HEX).unwrap()).unwrap();
+        assert_eq!(Lib::from_strict_serialized::<U24MAX>(data).unwrap(), lib);
+    }
+
+    #[test]
+    fn lib_site_strict_encoding() {
+        const HEX: &str = "a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5ab69";
+        let site = LibSite::new(LibId::from([0xA5u8; 32]), 0x69AB);
+        let data = site.to_strict_serialized::<U24MAX>().unwrap();
+        assert_eq!(data.to_hex(), HEX);
+        let data = Confined::try_from(Vec::<u8>::from_hex(HEX).unwrap()).unwrap();
+        assert_eq!(LibSite::from_strict_serialized::<U24MAX>(data).unwrap(), site);
+    }
 }
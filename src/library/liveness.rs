@@ -0,0 +1,284 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use super::{Lib, LibId};
+use crate::core::CoreExt;
+use crate::isa::{CodeEofError, Instruction};
+
+/// Registers which are live (i.e. which value may be read later) at each of the code offsets.
+pub type LivenessMap<Reg> = BTreeMap<u16, BTreeSet<Reg>>;
+
+impl Lib {
+    /// Performs def-use liveness analysis of the library code.
+    ///
+    /// Returns for offset of each instruction the set of registers which are live right before the
+    /// instruction is executed. The analysis is conservative: it iterates over cycles until a
+    /// fixed point is reached, and assumes that return instructions (see
+    /// [`Instruction::is_return`]) may return to any of the local call sites. No registers are
+    /// live after other instructions without successors, like `stop`.
+    pub fn compute_liveness<Isa>(
+        &self,
+    ) -> Result<LivenessMap<<Isa::Core as CoreExt>::Reg>, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let code = self.instr_offsets::<Isa>()?;
+        let (_, live) = liveness(&code);
+        Ok(code.iter().map(|(pos, _, _)| *pos).zip(live).collect())
+    }
+
+    /// Constructs register interference graph of the library code, in which two registers are
+    /// connected if they are simultaneously live at some point of the program, or if one of them
+    /// is written while the other one is live.
+    ///
+    /// Uses liveness analysis of [`Lib::compute_liveness`].
+    pub fn interference_graph<Isa>(
+        &self,
+    ) -> Result<InterferenceGraph<<Isa::Core as CoreExt>::Reg>, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let code = self.instr_offsets::<Isa>()?;
+        let (succ, live) = liveness(&code);
+
+        let mut graph = InterferenceGraph(BTreeMap::new());
+        for (idx, (_, _, instr)) in code.iter().enumerate() {
+            for reg in instr.regs() {
+                graph.0.entry(reg).or_default();
+            }
+            for a in &live[idx] {
+                for b in &live[idx] {
+                    graph.connect(*a, *b);
+                }
+            }
+            for dst in instr.dst_regs() {
+                for next in &succ[idx] {
+                    for reg in &live[*next] {
+                        graph.connect(dst, *reg);
+                    }
+                }
+            }
+        }
+        Ok(graph)
+    }
+}
+
+/// Undirected register interference graph, mapping each register to the set of registers it
+/// interferes with. Constructed with [`Lib::interference_graph`].
+#[derive(Wrapper, Clone, Eq, PartialEq, Debug, From)]
+#[wrapper(Deref)]
+pub struct InterferenceGraph<Reg: Ord>(BTreeMap<Reg, BTreeSet<Reg>>);
+
+impl<Reg: Ord + Copy> InterferenceGraph<Reg> {
+    fn connect(&mut self, a: Reg, b: Reg) {
+        if a != b {
+            self.0.entry(a).or_default().insert(b);
+            self.0.entry(b).or_default().insert(a);
+        }
+    }
+
+    /// Checks whether two registers interfere.
+    pub fn interferes(&self, a: Reg, b: Reg) -> bool {
+        self.0
+            .get(&a)
+            .map(|regs| regs.contains(&b))
+            .unwrap_or_default()
+    }
+
+    /// Returns lower bound of the chromatic number of the graph, i.e. of the number of distinct
+    /// registers required for the register colouring.
+    ///
+    /// The bound is the size of the largest clique found by growing a clique from each of the
+    /// registers greedily.
+    pub fn chromatic_number_lower_bound(&self) -> usize {
+        self.0
+            .iter()
+            .map(|(reg, neighbours)| {
+                let mut clique = vec![*reg];
+                for candidate in neighbours {
+                    if clique
+                        .iter()
+                        .all(|member| self.interferes(*member, *candidate))
+                    {
+                        clique.push(*candidate);
+                    }
+                }
+                clique.len()
+            })
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Performs liveness analysis of the decoded code, given as a list of instructions together with
+/// their offsets and offsets of the next instructions.
+///
+/// Returns successors of each of the instructions and registers live right before each of them.
+#[allow(clippy::type_complexity)]
+fn liveness<Isa>(
+    code: &[(u16, u16, Isa)],
+) -> (Vec<Vec<usize>>, Vec<BTreeSet<<Isa::Core as CoreExt>::Reg>>)
+where Isa: Instruction<LibId> {
+    let index = code
+        .iter()
+        .enumerate()
+        .map(|(idx, (pos, _, _))| (*pos, idx))
+        .collect::<BTreeMap<_, _>>();
+    let returns = code
+        .iter()
+        .filter(|(_, _, instr)| instr.local_call_pos().is_some())
+        .filter_map(|(_, next, _)| index.get(next).copied())
+        .collect::<Vec<_>>();
+    let succ = code
+        .iter()
+        .map(|(pos, next, instr)| {
+            let mut succ = instr
+                .successors(*pos, *next)
+                .filter_map(|target| index.get(&target).copied())
+                .collect::<Vec<_>>();
+            if instr.is_return() {
+                succ.extend(&returns);
+            }
+            succ
+        })
+        .collect::<Vec<_>>();
+    let src = code
+        .iter()
+        .map(|(_, _, instr)| instr.src_regs())
+        .collect::<Vec<_>>();
+    let dst = code
+        .iter()
+        .map(|(_, _, instr)| instr.dst_regs())
+        .collect::<Vec<_>>();
+
+    let mut live = vec![BTreeSet::new(); code.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for idx in (0..code.len()).rev() {
+            let mut regs = succ[idx]
+                .iter()
+                .flat_map(|next| live[*next].iter().copied())
+                .filter(|reg| !dst[idx].contains(reg))
+                .collect::<BTreeSet<_>>();
+            regs.extend(src[idx].iter().copied());
+            if regs != live[idx] {
+                live[idx] = regs;
+                changed = true;
+            }
+        }
+    }
+
+    (succ, live)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::test_utils::{Reg, TestInstr};
+    use crate::isa::CtrlInstr;
+
+    fn put(reg: u8) -> TestInstr { TestInstr::Load(reg) }
+    fn read(reg: u8) -> TestInstr { TestInstr::Read(reg) }
+    fn ctrl(instr: CtrlInstr<LibId>) -> TestInstr { TestInstr::Ctrl(instr) }
+
+    #[test]
+    fn dead_at_exit() {
+        let lib = Lib::assemble(&[put(0), put(1), read(0), ctrl(CtrlInstr::Stop)]).unwrap();
+        let live = lib.compute_liveness::<TestInstr>().unwrap();
+        assert_eq!(live[&0], none!());
+        assert_eq!(live[&2], bset![Reg(0)]);
+        assert_eq!(live[&4], bset![Reg(0)]);
+        assert_eq!(live[&6], none!());
+        assert!(live.values().all(|regs| !regs.contains(&Reg(1))));
+    }
+
+    #[test]
+    fn loops_and_calls() {
+        // 0: put R0; 2: read R1; 4: jif CO, 0; 7: stop
+        let lib = Lib::assemble(&[
+            put(0),
+            read(1),
+            ctrl(CtrlInstr::JiNe { pos: 0 }),
+            ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let live = lib.compute_liveness::<TestInstr>().unwrap();
+        assert_eq!(live[&0], bset![Reg(1)]);
+        assert_eq!(live[&4], bset![Reg(1)]);
+        assert_eq!(live[&7], none!());
+
+        // 0: call 5; 3: read R0; 5: put R0; 7: ret
+        let lib =
+            Lib::assemble(&[ctrl(CtrlInstr::Fn { pos: 5 }), read(0), put(0), ctrl(CtrlInstr::Ret)])
+                .unwrap();
+        let live = lib.compute_liveness::<TestInstr>().unwrap();
+        assert_eq!(live[&7], bset![Reg(0)]);
+        assert_eq!(live[&5], none!());
+
+        // 0: call 6; 3: read R0; 5: stop; 6: stop
+        let lib = Lib::assemble(&[
+            ctrl(CtrlInstr::Fn { pos: 6 }),
+            read(0),
+            ctrl(CtrlInstr::Stop),
+            ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let live = lib.compute_liveness::<TestInstr>().unwrap();
+        assert_eq!(live[&3], bset![Reg(0)]);
+        assert_eq!(live[&6], none!());
+        assert_eq!(live[&0], bset![Reg(0)]);
+    }
+
+    #[test]
+    fn interference() {
+        let lib =
+            Lib::assemble(&[put(0), read(0), put(1), read(1), ctrl(CtrlInstr::Stop)]).unwrap();
+        let graph = lib.interference_graph::<TestInstr>().unwrap();
+        assert_eq!(graph.len(), 2);
+        assert!(!graph.interferes(Reg(0), Reg(1)));
+        assert_eq!(graph.chromatic_number_lower_bound(), 1);
+
+        let lib = Lib::assemble(&[
+            put(0),
+            put(1),
+            put(2),
+            read(0),
+            read(1),
+            put(3),
+            read(2),
+            read(3),
+            ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let graph = lib.interference_graph::<TestInstr>().unwrap();
+        for (reg, neighbours) in graph.iter() {
+            assert!(neighbours
+                .iter()
+                .all(|other| graph.interferes(*other, *reg)));
+        }
+        assert!(graph.interferes(Reg(0), Reg(1)));
+        assert!(graph.interferes(Reg(3), Reg(2)));
+        assert!(!graph.interferes(Reg(0), Reg(3)));
+        assert_eq!(graph.chromatic_number_lower_bound(), 3);
+    }
+}
@@ -71,10 +71,10 @@ where
 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("Marshaller")
-            .field("bytecode", &SmallBlob::from_slice_checked(self.bytecode.as_ref()))
+            .field("bytecode", &SmallBlob::from_checked(self.bytecode.as_ref().to_vec()))
             .field("byte_pos", &self.byte_pos)
             .field("bit_pos", &self.bit_pos)
-            .field("data", &SmallBlob::from_slice_checked(self.data.as_ref()))
+            .field("data", &SmallBlob::from_checked(self.data.as_ref().to_vec()))
             .field("libs", &self.libs)
             .finish()
     }
@@ -194,7 +194,7 @@ where
     fn write(&mut self, value: u32, bit_count: u5) -> Result<(), CodeEofError> {
         let mut cnt = bit_count.to_u8();
         let value = ((value as u64) << (self.bit_pos.to_u8())).to_le_bytes();
-        let n_bytes = (cnt + self.bit_pos.to_u8() + 7) / 8;
+        let n_bytes = (cnt + self.bit_pos.to_u8()).div_ceil(8);
         for i in 0..n_bytes {
             if self.bytecode.as_ref().len() >= u16::MAX as usize {
                 return Err(CodeEofError);
@@ -270,6 +270,16 @@ where
     #[inline]
     fn is_eof(&self) -> bool { self.byte_pos as usize >= self.bytecode.as_ref().len() }
 
+    #[inline]
+    fn remaining_bytes(&self) -> u16 {
+        let remaining = self
+            .bytecode
+            .as_ref()
+            .len()
+            .saturating_sub(self.byte_pos as usize);
+        remaining.min(u16::MAX as usize) as u16
+    }
+
     fn peek_byte(&self) -> Result<u8, CodeEofError> {
         if self.is_eof() {
             return Err(CodeEofError);
@@ -341,9 +351,10 @@ where
     fn read_bytes(&mut self) -> Result<(SmallBlob, bool), CodeEofError> {
         let pos = self.read_word()? as usize;
         let end = pos + self.read_word()? as usize;
-        let ck = end >= self.data.as_ref().len();
-        let data = &self.data.as_ref()[pos.min(0xFF)..end.min(0xFF)];
-        Ok((SmallBlob::from_slice_checked(data), ck))
+        let len = self.data.as_ref().len();
+        let ck = end <= len;
+        let data = &self.data.as_ref()[pos.min(len)..end.min(len)];
+        Ok((SmallBlob::from_checked(data.to_vec()), ck))
     }
 
     fn read_ref(&mut self) -> Result<LibId, CodeEofError>
@@ -478,6 +489,58 @@ mod tests {
         assert!(marshaller.read_byte().is_err());
     }
 
+    #[test]
+    fn remaining_bytes() {
+        let libseg = LibsSeg::default();
+        let mut marshaller = Marshaller::with([0x00, 0x01, 0x02], [], &libseg);
+        assert_eq!(marshaller.remaining_bytes(), 3);
+        assert!(marshaller.is_at_boundary(3));
+        assert!(!marshaller.is_at_boundary(4));
+        marshaller.read_2bits().unwrap();
+        assert_eq!(marshaller.remaining_bytes(), 3);
+        marshaller.read_6bits().unwrap();
+        assert_eq!(marshaller.remaining_bytes(), 2);
+        marshaller.read_word().unwrap();
+        assert_eq!(marshaller.remaining_bytes(), 0);
+        assert!(marshaller.is_at_boundary(0));
+        assert!(!marshaller.is_at_boundary(1));
+    }
+
+    #[test]
+    fn read_bytes_outside_data() {
+        let libseg = LibsSeg::default();
+        let mut marshaller =
+            Marshaller::with([0x01, 0x00, 0x02, 0x00], [0xA0, 0xA1, 0xA2], &libseg);
+        let (data, ck) = marshaller.read_bytes().unwrap();
+        assert_eq!(data.as_slice(), &[0xA1, 0xA2]);
+        assert!(ck);
+
+        let mut marshaller =
+            Marshaller::with([0x02, 0x00, 0x10, 0x00], [0xA0, 0xA1, 0xA2], &libseg);
+        let (data, ck) = marshaller.read_bytes().unwrap();
+        assert_eq!(data.as_slice(), &[0xA2]);
+        assert!(!ck);
+
+        let mut marshaller =
+            Marshaller::with([0x00, 0x01, 0x10, 0x00], [0xA0, 0xA1, 0xA2], &libseg);
+        let (data, ck) = marshaller.read_bytes().unwrap();
+        assert!(data.is_empty());
+        assert!(!ck);
+
+        let data = (0..0x120).map(|i| i as u8).collect::<Vec<_>>();
+        let mut marshaller = Marshaller::with([0x00, 0x01, 0x02, 0x00], data, &libseg);
+        let (data, ck) = marshaller.read_bytes().unwrap();
+        assert_eq!(data.as_slice(), &[0x00, 0x01]);
+        assert!(ck);
+    }
+
+    #[test]
+    fn debug_empty() {
+        let libseg = LibsSeg::default();
+        let marshaller = Marshaller::with([], [], &libseg);
+        assert!(format!("{marshaller:?}").starts_with("Marshaller"));
+    }
+
     #[test]
     fn write() {
         let libseg = LibsSeg::default();
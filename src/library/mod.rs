@@ -28,6 +28,24 @@ pub mod armor;
 mod assembler;
 mod marshaller;
 mod exec;
+mod constants;
+mod complexity;
+mod diff;
+mod dot;
+mod gas;
+mod inline;
+mod liveness;
+mod validate;
+mod xor;
 
-pub use lib::{Lib, LibId, LibSite, LibsSeg};
+pub use assembler::{AssemblerError, InstrIter, ValidationError};
+pub use complexity::ComplexityAnalysisError;
+pub use constants::DataEntry;
+pub use diff::{InstrChange, LibDiff};
+pub use gas::{ComplexityProportionalGasModel, ConstantGasModel, GasModel};
+pub use inline::InlineError;
+pub use lib::{IsaeCompatError, Lib, LibId, LibSite, LibsSeg, LibsSegOverflow};
+pub use liveness::{InterferenceGraph, LivenessMap};
 pub use marshaller::{MarshallError, Marshaller};
+pub use validate::{DepError, JumpTargetError};
+pub use xor::{DecryptError, EncryptedLib};
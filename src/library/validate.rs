@@ -0,0 +1,284 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use alloc::collections::{BTreeMap, BTreeSet};
+
+use super::{Lib, LibId};
+use crate::core::Site;
+use crate::isa::{CodeEofError, Instruction};
+
+/// Errors detected while validating jump and call targets of library code.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum JumpTargetError {
+    /// incomplete instruction at offset {0:#06X}.
+    Incomplete(u16),
+
+    /// instruction at offset {from:#06X} jumps to offset {target:#06X}, which is not a start of
+    /// an instruction.
+    Local { from: u16, target: u16 },
+
+    /// instruction at offset {from:#06X} calls {site}, which is not a start of an instruction.
+    Remote { from: u16, site: Site<LibId> },
+
+    /// instruction at offset {from:#06X} calls library {lib_id}, which is unknown or can't be
+    /// disassembled.
+    UnknownLib { from: u16, lib_id: LibId },
+}
+
+/// Mismatches between the libraries declared in the library segment and the libraries which are
+/// actually called by the library code.
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display, Error, From)]
+#[display(doc_comments)]
+pub enum DepError {
+    /// unable to decode the library code.
+    #[from]
+    Decode(CodeEofError),
+
+    /// library {0} is declared as a dependency, but is never called.
+    Unused(LibId),
+
+    /// library {0} is called, but is not declared as a dependency.
+    Undeclared(LibId),
+}
+
+/// Offset of an instruction in the code segment which can't be decoded.
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub(super) struct IncompleteInstr(pub u16);
+
+impl From<IncompleteInstr> for CodeEofError {
+    fn from(_: IncompleteInstr) -> Self { CodeEofError }
+}
+
+impl From<IncompleteInstr> for JumpTargetError {
+    fn from(err: IncompleteInstr) -> Self { JumpTargetError::Incomplete(err.0) }
+}
+
+impl Lib {
+    /// Verifies that all local jump and call targets of the library code point to the start of
+    /// some instruction, and never into the middle of a multibyte instruction.
+    ///
+    /// Calls into other libraries are not checked; use [`Lib::validate_call_targets`] for that.
+    pub fn validate_jump_targets<Isa>(&self) -> Result<(), JumpTargetError>
+    where Isa: Instruction<LibId> {
+        let code = self.instr_offsets::<Isa>()?;
+        let offsets = code.iter().map(|(pos, _, _)| *pos).collect::<BTreeSet<_>>();
+        for (pos, next, instr) in &code {
+            for target in instr.successors(*pos, *next) {
+                if target != *next && !offsets.contains(&target) {
+                    return Err(JumpTargetError::Local { from: *pos, target });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies that all calls into other libraries point to the start of some instruction in the
+    /// called library code.
+    ///
+    /// Libraries are resolved with `lib_resolver`; a library which can't be resolved or
+    /// disassembled is reported as [`JumpTargetError::UnknownLib`].
+    pub fn validate_call_targets<'lib, Isa>(
+        &self,
+        lib_resolver: impl Fn(LibId) -> Option<&'lib Lib>,
+    ) -> Result<(), JumpTargetError>
+    where
+        Isa: Instruction<LibId>,
+    {
+        let mut libs = BTreeMap::<LibId, BTreeSet<u16>>::new();
+        for (pos, _, instr) in self.instr_offsets::<Isa>()? {
+            for site in instr.call_sites() {
+                let offsets = match libs.get(&site.prog_id) {
+                    Some(offsets) => offsets,
+                    None => {
+                        let unknown =
+                            || JumpTargetError::UnknownLib { from: pos, lib_id: site.prog_id };
+                        let lib = lib_resolver(site.prog_id).ok_or_else(unknown)?;
+                        let offsets = lib
+                            .iter_instructions::<Isa>()
+                            .map(|res| res.map(|(pos, _)| pos))
+                            .collect::<Result<BTreeSet<_>, _>>()
+                            .map_err(|_| unknown())?;
+                        libs.entry(site.prog_id).or_insert(offsets)
+                    }
+                };
+                if !offsets.contains(&site.offset) {
+                    return Err(JumpTargetError::Remote { from: pos, site });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Collects ids of all libraries which are called by the library code.
+    ///
+    /// Unlike [`Lib::dependency_ids`], which lists the libraries declared in the library segment,
+    /// this scans the code and returns only the libraries which are actually referenced.
+    pub fn extract_called_lib_ids<Isa>(&self) -> Result<BTreeSet<LibId>, CodeEofError>
+    where Isa: Instruction<LibId> {
+        let mut ids = BTreeSet::new();
+        for res in self.iter_instructions::<Isa>() {
+            let (_, instr) = res?;
+            ids.extend(instr.call_sites().map(|site| site.prog_id));
+        }
+        Ok(ids)
+    }
+
+    /// Verifies that the libraries declared in the library segment are exactly the ones called
+    /// by the library code.
+    pub fn validate_deps<Isa>(&self) -> Result<(), DepError>
+    where Isa: Instruction<LibId> {
+        let called = self.extract_called_lib_ids::<Isa>()?;
+        if let Some(id) = self.dependency_ids().find(|id| !called.contains(id)) {
+            return Err(DepError::Unused(id));
+        }
+        if let Some(id) = called.into_iter().find(|id| !self.has_dependency(*id)) {
+            return Err(DepError::Undeclared(id));
+        }
+        Ok(())
+    }
+
+    /// Decodes the code segment into a list of instructions together with their offsets and
+    /// offsets of the next instructions.
+    pub(super) fn instr_offsets<Isa>(&self) -> Result<Vec<(u16, u16, Isa)>, IncompleteInstr>
+    where Isa: Instruction<LibId> {
+        let mut code = Vec::new();
+        let mut iter = self.iter_instructions::<Isa>();
+        while let Some(res) = iter.next() {
+            let (pos, instr) = res.map_err(|_| IncompleteInstr(iter.pos()))?;
+            code.push((pos, iter.pos(), instr));
+        }
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::SmallBlob;
+
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+
+    #[test]
+    fn valid_jump_targets() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 0 }),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 10 }),
+            Instr::Ctrl(CtrlInstr::Sh { shift: -3 }),
+            Instr::Ctrl(CtrlInstr::Ret),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        assert_eq!(lib.validate_jump_targets::<Instr<LibId>>(), Ok(()));
+    }
+
+    #[test]
+    fn invalid_jump_targets() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos: 2 }),
+        ])
+        .unwrap();
+        assert_eq!(
+            lib.validate_jump_targets::<Instr<LibId>>(),
+            Err(JumpTargetError::Local { from: 1, target: 2 })
+        );
+
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::<LibId>::Ctrl(CtrlInstr::ShNe { shift: 1 }),
+        ])
+        .unwrap();
+        assert_eq!(
+            lib.validate_jump_targets::<Instr<LibId>>(),
+            Err(JumpTargetError::Local { from: 1, target: 2 })
+        );
+
+        let mut lib = lib;
+        lib.code = SmallBlob::from_checked(vec![0x00, 0x05]);
+        assert_eq!(
+            lib.validate_jump_targets::<Instr<LibId>>(),
+            Err(JumpTargetError::Incomplete(1))
+        );
+    }
+
+    #[test]
+    fn call_targets() {
+        let callee = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::<LibId>::Ctrl(CtrlInstr::Jmp { pos: 0 }),
+        ])
+        .unwrap();
+        let resolver = |id| (id == callee.lib_id()).then_some(&callee);
+
+        let good = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(callee.lib_id(), 1) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Exec { site: Site::new(callee.lib_id(), 0) }),
+        ])
+        .unwrap();
+        assert_eq!(good.validate_call_targets::<Instr<LibId>>(resolver), Ok(()));
+
+        let site = Site::new(callee.lib_id(), 2);
+        let bad = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(callee.lib_id(), 1) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Exec { site }),
+        ])
+        .unwrap();
+        assert_eq!(
+            bad.validate_call_targets::<Instr<LibId>>(resolver),
+            Err(JumpTargetError::Remote { from: 4, site })
+        );
+
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let unknown =
+            Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Call { site: Site::new(lib_id, 0) })])
+                .unwrap();
+        assert_eq!(
+            unknown.validate_call_targets::<Instr<LibId>>(resolver),
+            Err(JumpTargetError::UnknownLib { from: 0, lib_id })
+        );
+    }
+
+    #[test]
+    fn called_lib_ids() {
+        let id1 = LibId::from([1u8; 32]);
+        let id2 = LibId::from([2u8; 32]);
+        let mut lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Call { site: Site::new(id2, 1) }),
+            Instr::Ctrl(CtrlInstr::Exec { site: Site::new(id1, 0) }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Call { site: Site::new(id2, 4) }),
+        ])
+        .unwrap();
+        assert_eq!(lib.extract_called_lib_ids::<Instr<LibId>>(), Ok(bset![id1, id2]));
+        assert_eq!(lib.validate_deps::<Instr<LibId>>(), Ok(()));
+
+        let id3 = LibId::from([3u8; 32]);
+        lib.add_dependency(id3).unwrap();
+        assert_eq!(lib.validate_deps::<Instr<LibId>>(), Err(DepError::Unused(id3)));
+
+        lib.code = SmallBlob::from_checked(vec![0x00, 0x0D]);
+        assert_eq!(lib.validate_deps::<Instr<LibId>>(), Err(DepError::Decode(CodeEofError)));
+    }
+}
@@ -0,0 +1,178 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Designed in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+// Written in 2021-2025 by Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2024 LNP/BP Standards Association, Switzerland.
+// Copyright (C) 2024-2025 Laboratories for Ubiquitous Deterministic Computing (UBIDECO),
+//                         Institute for Distributed and Cognitive Systems (InDCS), Switzerland.
+// Copyright (C) 2021-2025 Dr Maxim Orlovsky.
+// All rights under the above copyrights are reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not use this file except
+// in compliance with the License. You may obtain a copy of the License at
+//
+//        http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software distributed under the License
+// is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express
+// or implied. See the License for the specific language governing permissions and limitations under
+// the License.
+
+use amplify::confinement::{SmallBlob, TinyOrdSet};
+use amplify::Bytes32;
+use commit_verify::{Digest, DigestExt, Sha256};
+
+use super::{Lib, LibId, LibsSeg};
+use crate::IsaId;
+
+/// Tag of the hash function deriving key stream blocks for [`Lib::xor_encrypt`].
+const XOR_KEY_STREAM_TAG: &str = "urn:ubideco:aluvm:xor-stream:v01#261014";
+/// Tag of the hash function computing [`EncryptedLib::mac`].
+const XOR_MAC_TAG: &str = "urn:ubideco:aluvm:xor-mac:v01#261014";
+
+/// Library with the code segment obfuscated with a key, produced by [`Lib::xor_encrypt`].
+///
+/// This is not a cryptographically strong encryption and must be used only for obfuscation of the
+/// intermediate build artifacts.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EncryptedLib {
+    /// Id of the original library.
+    pub lib_id: LibId,
+    /// ISA extensions segment of the original library.
+    pub isae: TinyOrdSet<IsaId>,
+    /// Code segment XOR-ed with the key stream.
+    pub code: SmallBlob,
+    /// Data segment of the original library, which is not obfuscated.
+    pub data: SmallBlob,
+    /// Libraries segment of the original library.
+    pub libs: LibsSeg,
+    /// Tagged hash of the key and the length-prefixed original code segment, used for the
+    /// integrity check.
+    pub mac: Bytes32,
+}
+
+/// Errors decrypting library with [`Lib::xor_decrypt`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum DecryptError {
+    /// integrity check of the decrypted library has failed; the key is invalid or the encrypted
+    /// library is corrupted.
+    InvalidMac,
+
+    /// decrypted library has id {actual}, while {expected} was expected.
+    LibIdMismatch { expected: LibId, actual: LibId },
+}
+
+impl Lib {
+    /// Obfuscates the code segment of the library by XOR-ing it with a key stream derived from
+    /// the key.
+    pub fn xor_encrypt(&self, key: &[u8; 32]) -> EncryptedLib {
+        let mut code = self.code.to_vec();
+        xor_key_stream(&mut code, key);
+        EncryptedLib {
+            lib_id: self.lib_id(),
+            isae: self.isae.clone(),
+            code: SmallBlob::from_checked(code),
+            data: self.data.clone(),
+            libs: self.libs.clone(),
+            mac: mac(key, &self.code),
+        }
+    }
+
+    /// Restores library obfuscated with [`Lib::xor_encrypt`], verifying its integrity.
+    pub fn xor_decrypt(enc: &EncryptedLib, key: &[u8; 32]) -> Result<Lib, DecryptError> {
+        let mut code = enc.code.to_vec();
+        xor_key_stream(&mut code, key);
+        if mac(key, &code) != enc.mac {
+            return Err(DecryptError::InvalidMac);
+        }
+        let lib = Lib::from_bytecode_unchecked(
+            enc.isae.clone(),
+            SmallBlob::from_checked(code),
+            enc.data.clone(),
+            enc.libs.clone(),
+        );
+        let actual = lib.lib_id();
+        if actual != enc.lib_id {
+            return Err(DecryptError::LibIdMismatch { expected: enc.lib_id, actual });
+        }
+        Ok(lib)
+    }
+}
+
+fn xor_key_stream(data: &mut [u8], key: &[u8; 32]) {
+    for (no, chunk) in data.chunks_mut(32).enumerate() {
+        let mut hasher = Sha256::from_tag(XOR_KEY_STREAM_TAG);
+        hasher.update(key);
+        hasher.update((no as u16).to_le_bytes());
+        let block = hasher.finalize();
+        for (byte, mask) in chunk.iter_mut().zip(block) {
+            *byte ^= mask;
+        }
+    }
+}
+
+fn mac(key: &[u8; 32], code: &[u8]) -> Bytes32 {
+    let mut hasher = Sha256::from_tag(XOR_MAC_TAG);
+    hasher.update(key);
+    hasher.input_with_len::<{ u16::MAX as usize }>(code);
+    Bytes32::from_byte_array(hasher.finalize())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::isa::{CtrlInstr, Instr};
+    use crate::Site;
+
+    #[test]
+    fn xor_roundtrip() {
+        let lib_id = LibId::from([0xA5u8; 32]);
+        let mut code = vec![Instr::Ctrl(CtrlInstr::Nop); 40];
+        code.push(Instr::Ctrl(CtrlInstr::Call { site: Site::new(lib_id, 0x69AB) }));
+        code.push(Instr::<LibId>::Ctrl(CtrlInstr::Stop));
+        let lib = Lib::assemble(&code).unwrap();
+
+        let key = [0x42u8; 32];
+        let enc = lib.xor_encrypt(&key);
+        assert_eq!(enc.lib_id, lib.lib_id());
+        assert_ne!(enc.code, lib.code);
+        assert_eq!(enc.code.len(), lib.code.len());
+        assert_eq!(Lib::xor_decrypt(&enc, &key), Ok(lib));
+
+        assert_eq!(Lib::xor_decrypt(&enc, &[0x24u8; 32]), Err(DecryptError::InvalidMac));
+    }
+
+    #[test]
+    fn xor_tampered() {
+        let lib = Lib::assemble(&[Instr::<LibId>::Ctrl(CtrlInstr::Stop)]).unwrap();
+        let key = [0x42u8; 32];
+
+        let mut enc = lib.xor_encrypt(&key);
+        enc.code = SmallBlob::from_checked(vec![enc.code[0] ^ 0x01]);
+        assert_eq!(Lib::xor_decrypt(&enc, &key), Err(DecryptError::InvalidMac));
+
+        let mut enc = lib.xor_encrypt(&key);
+        enc.data = SmallBlob::from_checked(vec![0xDE]);
+        let actual = Lib { data: enc.data.clone(), ..lib.clone() }.lib_id();
+        assert_eq!(
+            Lib::xor_decrypt(&enc, &key),
+            Err(DecryptError::LibIdMismatch { expected: lib.lib_id(), actual })
+        );
+    }
+
+    #[test]
+    fn mac_separated_from_key_stream() {
+        let key = [0x42u8; 32];
+        for no in [0u16, 1, 0x100] {
+            let mut stream = vec![0u8; 32 * (no as usize + 1)];
+            xor_key_stream(&mut stream, &key);
+            let block = &stream[32 * no as usize..];
+            assert_ne!(mac(&key, &no.to_le_bytes()).to_byte_array().as_slice(), block);
+        }
+    }
+}
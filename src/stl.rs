@@ -31,8 +31,9 @@ use crate::{CoreConfig, Lib, LibSite, LIB_NAME_ALUVM};
 
 /// Strict type id for the lib-old providing data types from this crate.
 pub const LIB_ID_ALUVM: &str =
-    "stl:t1kptI_t-R8Ei0Wa-e0m53SK-toGi5AC-si8GK5F-MbQp588#reward-accent-swim";
+    "stl:zHaNMtke-mYovaXm-kErBVhK-zyv_UTY-ihspbSS-q4I5bbs#barcode-tina-biology";
 
+#[allow(clippy::result_large_err)]
 fn _aluvm_stl() -> Result<TypeLib, CompileError> {
     LibBuilder::new(libname!(LIB_NAME_ALUVM), tiny_bset! {
         strict_types::stl::std_stl().to_dependency(),
@@ -24,12 +24,31 @@
 
 //! Alu virtual machine
 
+#[cfg(feature = "audit")]
+use alloc::vec::Vec;
+#[cfg(feature = "audit")]
+use core::fmt::{self, Display, Formatter};
 use core::marker::PhantomData;
 
-use crate::core::{Core, CoreConfig, CoreExt, Status};
+use crate::core::{Core, CoreConfig, CoreExt, Site, Status};
+#[cfg(feature = "audit")]
+use crate::core::{Register, RegisterChange, RegisterDiff, RegisterSnapshot};
 use crate::isa::{Instr, Instruction};
 use crate::library::{Lib, LibId, LibSite};
 
+/// Action taken by the virtual machine after a breakpoint handler returns.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum BreakpointAction {
+    /// Continue program execution.
+    Continue,
+    /// Halt program execution, setting `CK` to a failed state.
+    Halt,
+}
+
+/// Handler called by the virtual machine on each breakpoint instruction with the breakpoint id
+/// and the current state of the core registers.
+pub type BreakpointHandler<'h, Cx> = dyn FnMut(u8, &Core<LibId, Cx>) -> BreakpointAction + 'h;
+
 /// Alu virtual machine providing single-core execution environment
 #[derive(Clone, Debug, Default)]
 pub struct Vm<Isa = Instr<LibId>>
@@ -69,11 +88,73 @@ where Isa: Instruction<LibId>
         entry_point: LibSite,
         context: &Isa::Context<'_>,
         lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+    ) -> Status {
+        self.exec_with(entry_point, context, lib_resolver, |_, _, _| true)
+    }
+
+    /// Executes the program starting from the provided entry point using the provided core
+    /// registers instead of the VM core, leaving the VM itself unchanged.
+    ///
+    /// This allows evaluating the same program against many different initial register states
+    /// without cloning the VM.
+    ///
+    /// # Returns
+    ///
+    /// Core registers at the end of the program execution and the value of the `st0` register.
+    pub fn exec_pure<'prog>(
+        &self,
+        entry_point: LibSite,
+        core: Core<LibId, Isa::Core>,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+    ) -> (Core<LibId, Isa::Core>, Status) {
+        let mut vm = Vm::<Isa> { core, phantom: PhantomData };
+        let status = vm.exec(entry_point, context, lib_resolver);
+        (vm.core, status)
+    }
+
+    /// Executes the program starting from the provided entry point, calling `handler` each time a
+    /// breakpoint instruction is executed.
+    ///
+    /// Other execution methods treat breakpoint instructions as no-operation.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution.
+    pub fn exec_with_breakpoints<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+        handler: &mut BreakpointHandler<'_, Isa::Core>,
+    ) -> Status {
+        self.exec_with(entry_point, context, lib_resolver, |_, instr, core| {
+            match instr.breakpoint_id() {
+                Some(id) => handler(id, core) == BreakpointAction::Continue,
+                None => true,
+            }
+        })
+    }
+
+    /// Executes the program starting from the provided entry point, calling `guard` before the
+    /// execution of each instruction.
+    ///
+    /// If the `guard` returns `false`, the execution is halted and `CK` is set to a failed state.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution.
+    pub(crate) fn exec_with<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+        mut guard: impl FnMut(Site<LibId>, &Isa, &Core<LibId, Isa::Core>) -> bool,
     ) -> Status {
         let mut call = Some(entry_point);
         while let Some(ref mut site) = call {
             if let Some(lib) = lib_resolver(site.lib_id) {
-                call = lib.exec::<Isa>(site.offset, &mut self.core, context);
+                call = lib.exec_with::<Isa>(site.offset, &mut self.core, context, &mut guard);
             } else if let Some(pos) = site.offset.checked_add(1) {
                 site.offset = pos;
             } else {
@@ -83,3 +164,619 @@ where Isa: Instruction<LibId>
         self.core.ck()
     }
 }
+
+/// Execution statistics collected by [`Vm::bench_exec`].
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct BenchStats {
+    /// Time spent on the program execution, in nanoseconds.
+    pub wall_ns: u64,
+    /// Number of executed instructions.
+    pub instruction_count: u64,
+    /// Complexity accumulated in the `CA` register during the execution.
+    pub complexity_total: u64,
+    /// Number of executed instructions per nanosecond.
+    pub instructions_per_ns: f64,
+}
+
+/// Benchmarking of program execution.
+impl<Isa> Vm<Isa>
+where Isa: Instruction<LibId>
+{
+    /// Executes the program starting from the provided entry point, measuring execution time with
+    /// [`std::time::Instant`].
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution and execution statistics.
+    #[cfg(feature = "std")]
+    pub fn bench_exec<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+    ) -> (Status, BenchStats) {
+        let start = std::time::Instant::now();
+        let clock = || start.elapsed().as_nanos() as u64;
+        self.bench_exec_with_clock(entry_point, context, lib_resolver, clock)
+    }
+
+    /// Executes the program starting from the provided entry point, measuring execution time with
+    /// the provided `clock` function, which must return current time in nanoseconds.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution and execution statistics.
+    pub fn bench_exec_with_clock<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+        mut clock: impl FnMut() -> u64,
+    ) -> (Status, BenchStats) {
+        let mut instruction_count = 0u64;
+        let complexity_start = self.core.ca();
+        let start = clock();
+        let status = self.exec_with(entry_point, context, lib_resolver, |_, _, _| {
+            instruction_count += 1;
+            true
+        });
+        let wall_ns = clock().saturating_sub(start);
+        let instructions_per_ns =
+            if wall_ns == 0 { 0.0 } else { instruction_count as f64 / wall_ns as f64 };
+        let stats = BenchStats {
+            wall_ns,
+            instruction_count,
+            complexity_total: self.core.ca().saturating_sub(complexity_start),
+            instructions_per_ns,
+        };
+        (status, stats)
+    }
+}
+
+/// Single register mutation recorded by [`Vm::exec_audited`].
+#[cfg(feature = "audit")]
+#[derive(Clone, Debug)]
+pub struct AuditEntry<Reg: Register> {
+    /// Location of the instruction which has mutated the register.
+    pub site: LibSite,
+    /// Register and its values before and after the instruction execution.
+    pub change: RegisterChange<LibId, Reg>,
+}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> PartialEq for AuditEntry<Reg>
+where RegisterChange<LibId, Reg>: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool { self.site == other.site && self.change == other.change }
+}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> Eq for AuditEntry<Reg> where RegisterChange<LibId, Reg>: Eq {}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> Display for AuditEntry<Reg> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let site = Site::new(self.site.lib_id, self.site.offset);
+        write!(f, "{site}: {}", self.change)
+    }
+}
+
+/// Transcript of all register mutations happened during program execution, collected by
+/// [`Vm::exec_audited`].
+#[cfg(feature = "audit")]
+#[derive(Clone, Debug)]
+pub struct AuditLog<Reg: Register>(Vec<AuditEntry<Reg>>);
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> Default for AuditLog<Reg> {
+    fn default() -> Self { AuditLog(Vec::new()) }
+}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> PartialEq for AuditLog<Reg>
+where AuditEntry<Reg>: PartialEq
+{
+    fn eq(&self, other: &Self) -> bool { self.0 == other.0 }
+}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> Eq for AuditLog<Reg> where AuditEntry<Reg>: Eq {}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> AuditLog<Reg> {
+    /// Returns the number of recorded register mutations.
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether no register mutations were recorded.
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    /// Returns all recorded register mutations in the order of their execution.
+    pub fn entries(&self) -> &[AuditEntry<Reg>] { &self.0 }
+
+    fn record<Cx: CoreExt<Reg = Reg>>(
+        &mut self,
+        site: Site<LibId>,
+        before: &RegisterSnapshot<LibId, Reg>,
+        after: &Core<LibId, Cx>,
+    ) where
+        Reg::Value: PartialEq,
+    {
+        let site = LibSite::from(site);
+        for change in RegisterDiff::since(before, after).iter() {
+            // Complexity is accumulated by each of the instructions and is not a register write.
+            if matches!(change, RegisterChange::Ca { .. }) {
+                continue;
+            }
+            self.0.push(AuditEntry { site, change: change.clone() });
+        }
+    }
+}
+
+#[cfg(feature = "audit")]
+impl<Reg: Register> Display for AuditLog<Reg> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for entry in &self.0 {
+            writeln!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Auditing of program execution.
+#[cfg(feature = "audit")]
+impl<Isa> Vm<Isa>
+where Isa: Instruction<LibId>
+{
+    /// Executes the program starting from the provided entry point, recording every mutation of
+    /// the control registers (except `CA`) and of the core extension registers written by the
+    /// instructions.
+    ///
+    /// Before each instruction the control registers, including the call stack, and the
+    /// destination registers of the instruction (see [`Instruction::dst_regs`]) are copied into a
+    /// [`RegisterSnapshot`]; the rest of the core extension state is not copied. Thus, the
+    /// overhead is proportional to the call stack depth and the number of the written registers.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution and the audit log.
+    pub fn exec_audited<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+    ) -> (Status, AuditLog<<Isa::Core as CoreExt>::Reg>)
+    where
+        <<Isa::Core as CoreExt>::Reg as Register>::Value: PartialEq,
+    {
+        let mut log = AuditLog::default();
+        let mut pending = None;
+        let status = self.exec_with(entry_point, context, lib_resolver, |site, instr, core| {
+            if let Some((site, before)) = pending.take() {
+                log.record(site, &before, core);
+            }
+            pending = Some((site, RegisterSnapshot::take(core, instr.dst_regs())));
+            true
+        });
+        if let Some((site, before)) = pending {
+            log.record(site, &before, &self.core);
+        }
+        (status, log)
+    }
+}
+
+/// Guard limiting the number of instructions which can be executed by a [`Vm`].
+///
+/// Unlike the `CY` register, which is 16-bit, the guard tracks the absolute number of executed
+/// instructions as a `u64` value.
+#[derive(Debug)]
+pub struct CycleGuard<'vm, Isa = Instr<LibId>>
+where Isa: Instruction<LibId>
+{
+    vm: &'vm mut Vm<Isa>,
+    max_cycles: u64,
+    cycles: u64,
+    exhausted: bool,
+}
+
+impl<'vm, Isa> CycleGuard<'vm, Isa>
+where Isa: Instruction<LibId>
+{
+    /// Constructs a guard which halts the virtual machine once it attempts to execute more than
+    /// `max_cycles` instructions.
+    ///
+    /// A guard with `max_cycles` equal to `u64::MAX` doesn't limit the execution and doesn't count
+    /// the instructions, running the virtual machine with zero overhead.
+    pub fn new(vm: &'vm mut Vm<Isa>, max_cycles: u64) -> Self {
+        Self { vm, max_cycles, cycles: 0, exhausted: false }
+    }
+
+    /// Returns the maximum number of instructions which the guard allows to execute.
+    pub fn max_cycles(&self) -> u64 { self.max_cycles }
+
+    /// Returns the number of instructions executed under the guard.
+    pub fn cycles(&self) -> u64 { self.cycles }
+
+    /// Returns whether the guard has halted the execution, refusing to run an instruction beyond
+    /// the cycle limit.
+    ///
+    /// A program completing with exactly `max_cycles` executed instructions doesn't exhaust the
+    /// guard.
+    pub fn is_exhausted(&self) -> bool { self.exhausted }
+
+    /// Executes the program starting from the provided entry point, halting it with `CK` set to a
+    /// failed state once the cycle limit is reached.
+    ///
+    /// # Returns
+    ///
+    /// Value of the `st0` register at the end of the program execution.
+    pub fn exec<'prog>(
+        &mut self,
+        entry_point: LibSite,
+        context: &Isa::Context<'_>,
+        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+    ) -> Status {
+        if self.max_cycles == u64::MAX {
+            return self.vm.exec(entry_point, context, lib_resolver);
+        }
+        let max_cycles = self.max_cycles;
+        let cycles = &mut self.cycles;
+        let exhausted = &mut self.exhausted;
+        self.vm
+            .exec_with(entry_point, context, lib_resolver, |_, _, _| {
+                if *cycles >= max_cycles {
+                    *exhausted = true;
+                    return false;
+                }
+                *cycles += 1;
+                true
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::core::NoExt;
+    use crate::isa::test_utils::{Reg, Regs, TestInstr};
+    use crate::isa::{CtrlInstr, IsaId, ISA_DEBUG};
+
+    #[test]
+    fn complexity_unlimited() {
+        let code = [Instr::Ctrl(CtrlInstr::Nop), Instr::Ctrl(CtrlInstr::FailCk)];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Fail);
+        assert_eq!(vm.core.cf(), 1);
+    }
+
+    #[test]
+    fn complexity_overflow() {
+        let code = [Instr::Ctrl(CtrlInstr::Jmp { pos: 3 }), Instr::Ctrl(CtrlInstr::Stop)];
+        let lib = Lib::assemble(&code).unwrap();
+        let config = CoreConfig { complexity_lim: Some(1), ..default!() };
+        let mut vm = Vm::<Instr<LibId>>::with(config, ());
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Fail);
+    }
+
+    #[test]
+    fn ret_after_fn() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Fn { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::Ret),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let mut guard = CycleGuard::new(&mut vm, 100);
+        let status = guard.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(guard.cycles(), 3);
+        assert_eq!(vm.core.cp(), 0);
+    }
+
+    #[test]
+    fn cycle_guard_halts_loop() {
+        let code = [Instr::Ctrl(CtrlInstr::Nop), Instr::Ctrl(CtrlInstr::Jmp { pos: 0 })];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let mut guard = CycleGuard::new(&mut vm, 1001);
+        let status = guard.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Fail);
+        assert_eq!(guard.cycles(), 1001);
+        assert!(guard.is_exhausted());
+        assert_eq!(vm.core.cf(), 1);
+    }
+
+    #[test]
+    fn cycle_guard_below_limit() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let mut guard = CycleGuard::new(&mut vm, 3);
+        let status = guard.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(guard.cycles(), 3);
+        assert!(!guard.is_exhausted());
+        assert!(!vm.core.has_failed());
+    }
+
+    #[test]
+    fn cycle_guard_unlimited() {
+        let code = [Instr::Ctrl(CtrlInstr::Nop), Instr::Ctrl(CtrlInstr::Stop)];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let mut guard = CycleGuard::new(&mut vm, u64::MAX);
+        let status = guard.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(guard.cycles(), 0);
+    }
+
+    fn nested_calls() -> Lib {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Fn { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 8 }),
+            Instr::Ctrl(CtrlInstr::Ret),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 12 }),
+            Instr::Ctrl(CtrlInstr::Ret),
+            Instr::Ctrl(CtrlInstr::Ret),
+        ];
+        Lib::assemble(&code).unwrap()
+    }
+
+    #[test]
+    fn call_stack_size() {
+        let lib = nested_calls();
+        let config = CoreConfig { call_stack_size: 3, ..default!() };
+        let mut vm = Vm::<Instr<LibId>>::with(config, ());
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(vm.core.cp(), 0);
+        assert_eq!(vm.core.cs_lim(), 3);
+    }
+
+    #[test]
+    fn call_stack_size_exceeded() {
+        let lib = nested_calls();
+        let config = CoreConfig { call_stack_size: 2, ..default!() };
+        let mut vm = Vm::<Instr<LibId>>::with(config, ());
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Fail);
+        assert_eq!(vm.core.cp(), 2);
+    }
+
+    #[test]
+    fn call_stack_size_clamped() {
+        let config = CoreConfig { call_stack_size: 0xFFFF, ..default!() };
+        let core = Core::<LibId, NoExt, 16>::with(config, ());
+        assert_eq!(core.cs_lim(), 16);
+    }
+
+    #[test]
+    fn exec_pure() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::JiNe { pos: 5 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::FailCk),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let entry = LibSite::new(lib.lib_id(), 0);
+        let vm = Vm::<Instr<LibId>>::new();
+
+        let (core, status) = vm.exec_pure(entry, Core::new(), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Fail);
+        assert!(core.co());
+        assert_eq!(core.cf(), 1);
+
+        let mut initial = Core::new();
+        initial.set_co(true);
+        let (core, status) = vm.exec_pure(entry, initial, &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert!(!core.co());
+        assert_eq!(core.cf(), 0);
+
+        assert!(!vm.core.co());
+        assert_eq!(vm.core.ck(), Status::Ok);
+        assert_eq!(vm.core.cf(), 0);
+    }
+
+    #[test]
+    fn iter_call_stack() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Fn { pos: 4 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 8 }),
+            Instr::Ctrl(CtrlInstr::Ret),
+            Instr::Ctrl(CtrlInstr::Fn { pos: 12 }),
+            Instr::Ctrl(CtrlInstr::Ret),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert_eq!(vm.core.call_stack_depth(), 0);
+        assert_eq!(vm.core.iter_call_stack().count(), 0);
+
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(vm.core.call_stack_depth(), 3);
+        assert_eq!(vm.core.iter_call_stack().collect::<Vec<_>>(), vec![
+            Site::new(lib.lib_id(), 3),
+            Site::new(lib.lib_id(), 7),
+            Site::new(lib.lib_id(), 11),
+        ]);
+    }
+
+    #[test]
+    fn bench_exec_with_clock() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::Jmp { pos: 5 }),
+            Instr::Ctrl(CtrlInstr::FailCk),
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ];
+        let executed = [code[0], code[1], code[3], code[4]];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let mut time = 1000;
+        let clock = || {
+            time += 100;
+            time
+        };
+        let (status, stats) =
+            vm.bench_exec_with_clock(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib), clock);
+        assert_eq!(status, Status::Ok);
+        assert_eq!(stats, BenchStats {
+            wall_ns: 100,
+            instruction_count: 4,
+            complexity_total: executed.iter().map(Instruction::complexity).sum(),
+            instructions_per_ns: 0.04,
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn bench_exec() {
+        let code = [
+            Instr::Ctrl(CtrlInstr::Nop),
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let (status, stats) = vm.bench_exec(LibSite::new(lib.lib_id(), 0), &(), |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(stats.instruction_count, 3);
+        assert_eq!(stats.complexity_total, code.iter().map(Instruction::complexity).sum::<u64>());
+    }
+
+    #[test]
+    fn host_context() {
+        let lib = Lib::assemble(&[TestInstr::Ctrl(CtrlInstr::Nop), TestInstr::Load(0)]).unwrap();
+        let mut vm = Vm::<TestInstr>::new();
+        assert_eq!(vm.core.get(Reg(0)), None);
+        let host_value = 0xDEAD_BEEF_u64;
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &host_value, |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(vm.core.get(Reg(0)), Some(host_value));
+    }
+
+    #[test]
+    fn preloaded_registers() {
+        let core = Core::with_registers(default!(), (), &[(Reg(0), 42)]).unwrap();
+        assert_eq!(core.get(Reg(0)), Some(42));
+        assert_eq!(
+            Core::<LibId, Regs>::with_registers(default!(), (), &[(Reg(0), 1), (Reg(0), 2)])
+                .unwrap_err(),
+            crate::InitError(Reg(0))
+        );
+
+        let lib = Lib::assemble(&[TestInstr::Ctrl(CtrlInstr::Nop)]).unwrap();
+        let mut vm = Vm::<TestInstr>::new();
+        vm.core = core;
+        let status = vm.exec(LibSite::new(lib.lib_id(), 0), &0, |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(vm.core.get(Reg(0)), Some(42));
+    }
+
+    #[test]
+    #[cfg(feature = "audit")]
+    fn audit_log() {
+        let lib = Lib::assemble(&[
+            TestInstr::Load(0),
+            TestInstr::Ctrl(CtrlInstr::NotCo),
+            TestInstr::Ctrl(CtrlInstr::Nop),
+            TestInstr::Ctrl(CtrlInstr::NotCo),
+            TestInstr::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let lib_id = lib.lib_id();
+        let mut vm = Vm::<TestInstr>::new();
+        let (status, log) = vm.exec_audited(LibSite::new(lib_id, 0), &42, |_| Some(&lib));
+        assert_eq!(status, Status::Ok);
+        assert_eq!(vm.core.get(Reg(0)), Some(42));
+        assert_eq!(log.len(), 3);
+        assert_eq!(log.entries()[0], AuditEntry {
+            site: LibSite::new(lib_id, 0),
+            change: RegisterChange::Cx { reg: Reg(0), old: None, new: Some(42) },
+        });
+        assert_eq!(log.entries()[1], AuditEntry {
+            site: LibSite::new(lib_id, 2),
+            change: RegisterChange::Co { old: false, new: true },
+        });
+        assert_eq!(log.entries()[2], AuditEntry {
+            site: LibSite::new(lib_id, 4),
+            change: RegisterChange::Co { old: true, new: false },
+        });
+        assert!(log
+            .to_string()
+            .starts_with(&format!("{}: R0: ~ → 42\n", Site::new(lib_id, 0))));
+    }
+
+    #[test]
+    fn breakpoint() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::Breakpoint { id: 1 }),
+            Instr::Ctrl(CtrlInstr::NotCo),
+            Instr::Ctrl(CtrlInstr::Breakpoint { id: 2 }),
+            Instr::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        assert!(lib.isae.contains(&IsaId::from(ISA_DEBUG)));
+        let entry = LibSite::new(lib.lib_id(), 0);
+
+        let mut vm = Vm::<Instr<LibId>>::new();
+        assert_eq!(vm.exec(entry, &(), |_| Some(&lib)), Status::Ok);
+
+        let mut hits = Vec::new();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec_with_breakpoints(entry, &(), |_| Some(&lib), &mut |id, core| {
+            hits.push((id, core.co()));
+            BreakpointAction::Continue
+        });
+        assert_eq!(status, Status::Ok);
+        assert_eq!(hits, vec![(1, true), (2, false)]);
+
+        let mut hits = Vec::new();
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec_with_breakpoints(entry, &(), |_| Some(&lib), &mut |id, core| {
+            hits.push((id, core.co()));
+            match id {
+                1 => BreakpointAction::Halt,
+                _ => BreakpointAction::Continue,
+            }
+        });
+        assert_eq!(status, Status::Fail);
+        assert_eq!(hits, vec![(1, true)]);
+        assert!(vm.core.co());
+    }
+
+    #[test]
+    fn breakpoint_undeclared_isae() {
+        let lib = Lib::assemble(&[
+            Instr::Ctrl(CtrlInstr::Breakpoint { id: 1 }),
+            Instr::<LibId>::Ctrl(CtrlInstr::Stop),
+        ])
+        .unwrap();
+        let lib = Lib::from_bytecode_unchecked(none!(), lib.code, lib.data, lib.libs);
+        let entry = LibSite::new(lib.lib_id(), 0);
+
+        let mut hits = 0;
+        let mut vm = Vm::<Instr<LibId>>::new();
+        let status = vm.exec_with_breakpoints(entry, &(), |_| Some(&lib), &mut |_, _| {
+            hits += 1;
+            BreakpointAction::Continue
+        });
+        assert_eq!(status, Status::Fail);
+        assert_eq!(hits, 0);
+        assert_eq!(vm.core.ca(), u64::MAX);
+    }
+}